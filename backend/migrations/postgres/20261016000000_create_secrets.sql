@@ -0,0 +1,9 @@
+-- Secrets are stored as the JSON-serialized `Secret` record, alongside the
+-- expiry instant so reads can check it and expired rows can be purged cheaply.
+CREATE TABLE IF NOT EXISTS secrets (
+    id TEXT PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS secrets_expires_at ON secrets (expires_at);
@@ -0,0 +1,2 @@
+-- Wrong PINs entered so far; the row is deleted once the limit is reached.
+ALTER TABLE secrets ADD COLUMN failed_pin_attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- Tombstones record why a secret is gone (read, expired, revoked, ...) for a
+-- while after it is deleted, as the JSON-serialized `Tombstone`.
+CREATE TABLE IF NOT EXISTS tombstones (
+    id TEXT PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS tombstones_expires_at ON tombstones (expires_at);
@@ -0,0 +1,9 @@
+-- Secrets are stored as the JSON-serialized `Secret` record, alongside the
+-- expiry instant (unix seconds) so expired rows can be purged with an index scan.
+CREATE TABLE IF NOT EXISTS secrets (
+    id TEXT PRIMARY KEY NOT NULL,
+    expires_at INTEGER NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS secrets_expires_at ON secrets (expires_at);
@@ -0,0 +1,3 @@
+-- Multi-view secrets: reads decrement this counter and the row is deleted when
+-- it reaches zero. Existing rows were created as single-view secrets.
+ALTER TABLE secrets ADD COLUMN remaining_views INTEGER NOT NULL DEFAULT 1;
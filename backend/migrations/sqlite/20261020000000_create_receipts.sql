@@ -0,0 +1,10 @@
+-- Read receipts tell senders what became of a secret, keyed by their own
+-- random id and kept for a while after the secret is gone, as the
+-- JSON-serialized `Receipt`.
+CREATE TABLE IF NOT EXISTS receipts (
+    id TEXT PRIMARY KEY NOT NULL,
+    expires_at INTEGER NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS receipts_expires_at ON receipts (expires_at);
@@ -0,0 +1,10 @@
+-- Maps an `Idempotency-Key` (stored hashed) to the secret its first request
+-- created, as the JSON-serialized `IdempotencyRecord`, for as long as retries
+-- are honoured.
+CREATE TABLE IF NOT EXISTS idempotency_records (
+    key TEXT PRIMARY KEY NOT NULL,
+    expires_at INTEGER NOT NULL,
+    body TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_records_expires_at ON idempotency_records (expires_at);
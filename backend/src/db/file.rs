@@ -0,0 +1,686 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{SecretStore, StorageError, StorageResult, is_valid_id};
+use crate::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+
+const SECRET_EXTENSION: &str = "secret";
+const CLAIMED_EXTENSION: &str = "claimed";
+const TOMBSTONE_EXTENSION: &str = "tombstone";
+const RECEIPT_EXTENSION: &str = "receipt";
+const IDEMPOTENCY_EXTENSION: &str = "idempotency";
+const TEMP_EXTENSION: &str = "tmp";
+
+/// How long a reader waits for another reader to put back a multi-view secret
+/// it has claimed.
+const CLAIM_WAIT: Duration = Duration::from_secs(1);
+
+/// Claims and temporary files are gone within milliseconds; one older than
+/// this was left behind by a process that crashed mid-way.
+const ABANDONED_FILE_AGE_SECS: i64 = 60;
+
+/// Filesystem-backed implementation of `SecretStore` for air-gapped appliances.
+///
+/// Every secret is a JSON file readable only by the service user (mode 0600)
+/// inside a private directory. A read first claims the file by renaming it to
+/// a name of its own, `<id>.<uuid>.claimed`; the rename is atomic, so exactly
+/// one reader finds the original still in place. If views remain, the winner
+/// writes the updated record back under the original name; either way it then
+/// overwrites the claimed copy with zeros before unlinking it. Readers that
+/// lose the race while a claim is in flight wait briefly for the record to
+/// reappear. A reader that crashes while holding a claim takes the secret's
+/// remaining views with it; the sweeper started with
+/// [`FileSecretStore::spawn_sweeper`] shreds such leftover claims along with
+/// expired files.
+pub struct FileSecretStore {
+    /// Shared with the sweeper only through a weak reference, so dropping the
+    /// store stops the sweeper.
+    dir: Arc<PathBuf>,
+}
+
+impl FileSecretStore {
+    /// Use `dir` as the secret directory, creating it with mode 0700 if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> StorageResult<Self> {
+        let dir = dir.into();
+
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)?;
+
+        Ok(Self { dir: Arc::new(dir) })
+    }
+
+    /// Start a background task that shreds expired files every `interval`.
+    ///
+    /// The task only holds a weak reference to the store's directory and
+    /// exits once the store has been dropped.
+    pub fn spawn_sweeper(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let dir = Arc::downgrade(&self.dir);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(dir) = dir.upgrade() else {
+                    break;
+                };
+                let swept = tokio::task::spawn_blocking(move || {
+                    purge_expired(&dir, OffsetDateTime::now_utc())
+                })
+                .await;
+
+                match swept {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(purged)) => {
+                        tracing::debug!(purged, "swept expired secrets from file store")
+                    }
+                    Ok(Err(err)) => tracing::warn!("failed to sweep file store: {:?}", err),
+                    Err(err) => tracing::warn!("file store sweeper panicked: {}", err),
+                }
+            }
+        })
+    }
+
+    async fn blocking<T, F>(&self, f: F) -> StorageResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Path) -> StorageResult<T> + Send + 'static,
+    {
+        let dir = self.dir.clone();
+
+        tokio::task::spawn_blocking(move || f(&dir))
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?
+    }
+}
+
+fn secret_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{SECRET_EXTENSION}"))
+}
+
+fn tombstone_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{TOMBSTONE_EXTENSION}"))
+}
+
+fn receipt_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{RECEIPT_EXTENSION}"))
+}
+
+fn idempotency_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{key}.{IDEMPOTENCY_EXTENSION}"))
+}
+
+/// A claim name no other reader will pick, so claims never clobber each other.
+fn claimed_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.{}.{CLAIMED_EXTENSION}", Uuid::new_v4()))
+}
+
+fn write_secret(dir: &Path, secret: &Secret) -> StorageResult<()> {
+    write_json(dir, &secret_path(dir, &secret.id), secret)
+}
+
+/// Write `value` as JSON to `path`, replacing whatever was there.
+fn write_json(dir: &Path, path: &Path, value: &impl Serialize) -> StorageResult<()> {
+    let tmp = write_temp_json(dir, value)?;
+    fs::rename(&tmp, path)?;
+    sync_dir(dir)
+}
+
+/// Flush `dir` itself, so that names linked into or renamed within it survive
+/// a crash.
+fn sync_dir(dir: &Path) -> StorageResult<()> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Write `value` as JSON to a private temporary file in `dir`, so readers
+/// never see a partial file once it is moved into place.
+fn write_temp_json(dir: &Path, value: &impl Serialize) -> StorageResult<PathBuf> {
+    let json = serde_json::to_vec(value)?;
+
+    let tmp = temp_path(dir);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)?;
+    file.write_all(&json)?;
+    file.sync_all()?;
+
+    Ok(tmp)
+}
+
+fn temp_path(dir: &Path) -> PathBuf {
+    dir.join(format!(".{}.{TEMP_EXTENSION}", Uuid::new_v4()))
+}
+
+/// Save `record` under `key` unless a live record is already there, which
+/// is returned instead.
+fn put_idempotency_record(
+    dir: &Path,
+    key: &str,
+    record: &IdempotencyRecord,
+    now: OffsetDateTime,
+) -> StorageResult<Option<IdempotencyRecord>> {
+    let path = idempotency_path(dir, key);
+    let tmp = write_temp_json(dir, record)?;
+
+    let result = loop {
+        // Unlike renaming, linking fails if something is already there.
+        match fs::hard_link(&tmp, &path) {
+            Ok(()) => break sync_dir(dir).map(|()| None),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+            Err(err) => break Err(err.into()),
+        }
+
+        match read_json::<IdempotencyRecord>(&path) {
+            Ok(Some(existing)) if !existing.is_expired_at(now) => break Ok(Some(existing)),
+            Ok(_) | Err(StorageError::Serialization(_)) => {}
+            Err(err) => break Err(err),
+        }
+
+        // Move the expired record aside before trying again. A racer may
+        // have replaced it in the meantime, in which case its record goes
+        // back where it was.
+        let aside = temp_path(dir);
+        match fs::rename(&path, &aside) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => break Err(err.into()),
+        }
+        if let Ok(Some(moved)) = read_json::<IdempotencyRecord>(&aside) {
+            if !moved.is_expired_at(now) {
+                let _ = fs::hard_link(&aside, &path);
+            }
+        }
+        // Renaming keeps the old modification time, so the sweeper may
+        // already have taken the file for an abandoned one.
+        match fs::remove_file(&aside) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => break Err(err.into()),
+        }
+    };
+
+    fs::remove_file(&tmp)?;
+    result
+}
+
+/// Atomically take ownership of the secret `id` by renaming it to a claim
+/// name of this reader's own.
+///
+/// Returns `None` if the secret is gone or another reader (or the sweeper)
+/// holds the claim.
+fn claim(dir: &Path, id: &str) -> StorageResult<Option<PathBuf>> {
+    let claimed = claimed_path(dir, id);
+
+    match fs::rename(secret_path(dir, id), &claimed) {
+        Ok(()) => Ok(Some(claimed)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether some reader currently holds a claim on the secret `id`.
+fn has_pending_claim(dir: &Path, id: &str) -> StorageResult<bool> {
+    let prefix = format!("{id}.");
+    let suffix = format!(".{CLAIMED_EXTENSION}");
+
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(name) = name.to_str() {
+            if name.starts_with(&prefix) && name.ends_with(&suffix) {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Run `attempt` until it finds the secret `id`, waiting out other readers'
+/// claims on it.
+fn wait_for_claims<T>(
+    dir: &Path,
+    id: &str,
+    mut attempt: impl FnMut() -> StorageResult<Option<T>>,
+) -> StorageResult<Option<T>> {
+    let deadline = std::time::Instant::now() + CLAIM_WAIT;
+
+    loop {
+        if let Some(found) = attempt()? {
+            return Ok(Some(found));
+        }
+        // A missing file with a live claim means another reader is about to
+        // write back a multi-view secret; anything else really is gone.
+        if !has_pending_claim(dir, id)? || std::time::Instant::now() >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Claim the secret `id` and apply `update` to it, then write it back or, if
+/// `update` returns `true`, shred it.
+///
+/// Returns the updated record, or `None` if the secret does not exist or has
+/// expired (in which case it is shredded).
+fn modify_secret(
+    dir: &Path,
+    id: &str,
+    now: OffsetDateTime,
+    update: impl FnOnce(&mut Secret) -> bool,
+) -> StorageResult<Option<Secret>> {
+    let Some(claimed) = wait_for_claims(dir, id, || claim(dir, id))? else {
+        return Ok(None);
+    };
+
+    let mut secret = match serde_json::from_slice::<Secret>(&fs::read(&claimed)?) {
+        Ok(secret) => secret,
+        Err(err) => {
+            shred(&claimed)?;
+            return Err(err.into());
+        }
+    };
+
+    // Expired secrets are burned without being returned.
+    if secret.is_expired_at(now) {
+        shred(&claimed)?;
+        write_expiry_tombstone(dir, id, &secret, now)?;
+        return Ok(None);
+    }
+
+    // Put the record back, or the tombstone of its last read in its place,
+    // before releasing the claim, so waiting readers never observe a gap.
+    if !update(&mut secret) {
+        write_secret(dir, &secret)?;
+    } else if let Some(tombstone) = secret.read_tombstone() {
+        write_json(dir, &tombstone_path(dir, id), &tombstone)?;
+    }
+    shred(&claimed)?;
+
+    Ok(Some(secret))
+}
+
+/// Leave the tombstone of a secret that ran out unread, if it asked for one.
+fn write_expiry_tombstone(
+    dir: &Path,
+    id: &str,
+    secret: &Secret,
+    now: OffsetDateTime,
+) -> StorageResult<()> {
+    match secret.expiry_tombstone() {
+        Some(tombstone) if !tombstone.is_expired_at(now) => {
+            write_json(dir, &tombstone_path(dir, id), &tombstone)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Read a claimed file, then overwrite it with zeros and unlink it.
+fn shred(path: &Path) -> StorageResult<Vec<u8>> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&vec![0u8; contents.len()])?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::remove_file(path)?;
+    Ok(contents)
+}
+
+/// The part of a tombstone or receipt file the sweeper cares about.
+#[derive(Deserialize)]
+struct Expiry {
+    expires_at: OffsetDateTime,
+}
+
+fn purge_expired(dir: &Path, now: OffsetDateTime) -> StorageResult<usize> {
+    let mut purged = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(SECRET_EXTENSION) => {
+                let expired = match read_secret(&path) {
+                    Ok(Some(secret)) => secret.is_expired_at(now),
+                    Ok(None) => false,
+                    // An unreadable secret can never be served, so burn it.
+                    Err(_) => true,
+                };
+
+                let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                if !expired {
+                    continue;
+                }
+                if let Some(claimed) = claim(dir, id)? {
+                    let contents = shred(&claimed)?;
+                    if let Ok(secret) = serde_json::from_slice::<Secret>(&contents) {
+                        write_expiry_tombstone(dir, id, &secret, now)?;
+                    }
+                    purged += 1;
+                }
+            }
+            // Claimed files are normally shredded immediately; one left behind
+            // means a reader crashed between claiming and shredding it, so
+            // finish the job.
+            Some(CLAIMED_EXTENSION) => {
+                // Renaming updates ctime, so it records when the claim was taken.
+                let metadata = match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                if now.unix_timestamp() - metadata.ctime() < ABANDONED_FILE_AGE_SECS {
+                    continue;
+                }
+                remove_abandoned(&path, &metadata);
+            }
+            // Temporary files are renamed or linked into place right after
+            // they are written; one left behind may hold a secret.
+            Some(TEMP_EXTENSION) => {
+                let metadata = match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                if now.unix_timestamp() - metadata.mtime() < ABANDONED_FILE_AGE_SECS {
+                    continue;
+                }
+                remove_abandoned(&path, &metadata);
+            }
+            // Tombstones and receipts hold no secret material, so they are
+            // simply unlinked.
+            Some(TOMBSTONE_EXTENSION | RECEIPT_EXTENSION | IDEMPOTENCY_EXTENSION) => {
+                let expired = match read_json::<Expiry>(&path) {
+                    Ok(Some(expiry)) => now >= expiry.expires_at,
+                    Ok(None) => false,
+                    Err(_) => true,
+                };
+                if !expired {
+                    continue;
+                }
+                if let Err(err) = fs::remove_file(&path) {
+                    if err.kind() != ErrorKind::NotFound {
+                        return Err(err.into());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Shred a file a crashed process left behind. One still linked elsewhere
+/// shares its contents with a live record, so it is only unlinked.
+fn remove_abandoned(path: &Path, metadata: &fs::Metadata) {
+    let result = if metadata.nlink() > 1 {
+        fs::remove_file(path).map_err(StorageError::from)
+    } else {
+        shred(path).map(drop)
+    };
+
+    if let Err(err) = result {
+        tracing::warn!("failed to remove abandoned file {:?}: {:?}", path, err);
+    }
+}
+
+fn read_secret(path: &Path) -> StorageResult<Option<Secret>> {
+    read_json(path)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> StorageResult<Option<T>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+
+    Ok(Some(serde_json::from_slice(&contents)?))
+}
+
+#[async_trait]
+impl SecretStore for FileSecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        let to_write = secret.clone();
+
+        self.blocking(move |dir| write_secret(dir, &to_write))
+            .await?;
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        self.blocking(move |dir| {
+            let now = OffsetDateTime::now_utc();
+            modify_secret(dir, &id, now, |secret| secret.consume_view(now))
+        })
+        .await
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        self.blocking(move |dir| {
+            let now = OffsetDateTime::now_utc();
+            let secret = wait_for_claims(dir, &id, || read_secret(&secret_path(dir, &id)))?;
+            Ok(secret.filter(|secret| !secret.is_expired_at(now)))
+        })
+        .await
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        let secret = self
+            .blocking(move |dir| {
+                modify_secret(dir, &id, OffsetDateTime::now_utc(), |secret| {
+                    secret.record_failed_pin(max_attempts) == 0
+                })
+            })
+            .await?;
+
+        Ok(secret.map(|secret| max_attempts.saturating_sub(secret.failed_pin_attempts)))
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        if !is_valid_id(id) {
+            return Ok(false);
+        }
+
+        let id = id.to_string();
+        let secret = self
+            .blocking(move |dir| modify_secret(dir, &id, OffsetDateTime::now_utc(), |_| true))
+            .await?;
+
+        Ok(secret.is_some())
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        self.blocking(move |dir| {
+            modify_secret(dir, &id, OffsetDateTime::now_utc(), |secret| {
+                secret.ttl_secs = ttl_secs;
+                false
+            })
+        })
+        .await
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        let id = id.to_string();
+        let tombstone = tombstone.clone();
+        self.blocking(move |dir| write_json(dir, &tombstone_path(dir, &id), &tombstone))
+            .await
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        let (tombstone, secret) = self
+            .blocking(move |dir| {
+                let tombstone = read_json::<Tombstone>(&tombstone_path(dir, &id))?;
+                // Expired secrets stay on disk until the sweeper gets to them.
+                let secret = match tombstone {
+                    Some(_) => None,
+                    None => read_secret(&secret_path(dir, &id))?,
+                };
+                Ok((tombstone, secret))
+            })
+            .await?;
+
+        let now = OffsetDateTime::now_utc();
+        let tombstone = tombstone.or_else(|| {
+            secret
+                .filter(|secret| secret.is_expired_at(now))
+                .and_then(|secret| secret.expiry_tombstone())
+        });
+        Ok(tombstone.filter(|tombstone| !tombstone.is_expired_at(now)))
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        if !is_valid_id(&receipt.id) {
+            return Ok(());
+        }
+
+        let receipt = receipt.clone();
+        self.blocking(move |dir| write_json(dir, &receipt_path(dir, &receipt.id), &receipt))
+            .await
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let id = id.to_string();
+        let receipt = self
+            .blocking(move |dir| read_json::<Receipt>(&receipt_path(dir, &id)))
+            .await?;
+
+        Ok(receipt.filter(|receipt| !receipt.is_expired_at(OffsetDateTime::now_utc())))
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        let id = id.to_string();
+        self.blocking(move |dir| match fs::remove_file(receipt_path(dir, &id)) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        })
+        .await
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        // Keys are hashes of the client's header, never the header itself.
+        debug_assert!(is_valid_id(key), "idempotency key is not a valid file name");
+
+        let key = key.to_string();
+        let record = record.clone();
+        self.blocking(move |dir| {
+            put_idempotency_record(dir, &key, &record, OffsetDateTime::now_utc())
+        })
+        .await
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        if !is_valid_id(key) {
+            return Ok(None);
+        }
+
+        let key = key.to_string();
+        let record = self
+            .blocking(move |dir| read_json::<IdempotencyRecord>(&idempotency_path(dir, &key)))
+            .await?;
+
+        Ok(record.filter(|record| !record.is_expired_at(OffsetDateTime::now_utc())))
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        if !is_valid_id(key) {
+            return Ok(());
+        }
+
+        let key = key.to_string();
+        let record = record.clone();
+        self.blocking(move |dir| write_json(dir, &idempotency_path(dir, &key), &record))
+            .await
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        self.blocking(|dir| {
+            let metadata = fs::metadata(dir)?;
+            if !metadata.is_dir() {
+                return Err(StorageError::Backend(format!(
+                    "{} is not a directory",
+                    dir.display()
+                )));
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Every write is synced to disk before it is acknowledged.
+    async fn is_durable(&self) -> StorageResult<bool> {
+        Ok(true)
+    }
+}
@@ -0,0 +1,1154 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+
+use crate::models::{IdempotencyRecord, Receipt, Secret, SecretMetadata, Tombstone};
+
+mod file;
+mod postgres;
+mod redis;
+mod registry;
+mod s3;
+mod sqlite;
+
+pub use file::FileSecretStore;
+pub use postgres::PostgresSecretStore;
+pub use redis::{RedisClientCert, RedisPoolConfig, RedisSecretStore, RedisSecurity, RedisTopology};
+pub use registry::{SecretStoreFactory, StoreRegistry};
+pub use s3::{S3SecretStore, S3StoreConfig};
+pub use sqlite::SqliteSecretStore;
+
+/// Errors that can occur when interacting with the secret storage backend.
+///
+/// Backends classify their native errors into these variants so the API can
+/// tell clients whether (and when) a retry makes sense.
+#[derive(Debug)]
+pub enum StorageError {
+    /// A generic backend error with a human-readable message.
+    Backend(String),
+    /// The backend cannot be reached or is temporarily refusing work (loading,
+    /// failing over, overloaded). Retrying shortly may succeed.
+    Unavailable(String),
+    /// The backend did not answer in time.
+    Timeout(String),
+    /// A record could not be serialized, or a stored record is corrupt.
+    Serialization(String),
+    /// The store has reached its configured capacity and refuses new secrets.
+    CapacityExceeded(String),
+    /// A concurrent writer got there first, or the write clashes with an
+    /// existing record.
+    Conflict(String),
+    /// The store's settings are invalid, so retrying cannot help.
+    Config(String),
+}
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+impl From<::redis::RedisError> for StorageError {
+    fn from(err: ::redis::RedisError) -> Self {
+        use ::redis::ErrorKind;
+
+        let message = err.to_string();
+        if err.is_timeout() {
+            return StorageError::Timeout(message);
+        }
+        if err.is_connection_refusal() || err.is_connection_dropped() || err.is_io_error() {
+            return StorageError::Unavailable(message);
+        }
+        // `maxmemory` was reached and the eviction policy refuses writes.
+        if err.code() == Some("OOM") {
+            return StorageError::CapacityExceeded(message);
+        }
+
+        match err.kind() {
+            ErrorKind::BusyLoadingError
+            | ErrorKind::TryAgain
+            | ErrorKind::ClusterDown
+            | ErrorKind::MasterDown
+            | ErrorKind::ReadOnly
+            | ErrorKind::MasterNameNotFoundBySentinel
+            | ErrorKind::NoValidReplicasFoundBySentinel
+            | ErrorKind::ClusterConnectionNotFound => StorageError::Unavailable(message),
+            ErrorKind::TypeError | ErrorKind::ParseError => StorageError::Serialization(message),
+            ErrorKind::AuthenticationFailed | ErrorKind::InvalidClientConfig => {
+                StorageError::Config(message)
+            }
+            _ => StorageError::Backend(message),
+        }
+    }
+}
+
+impl From<sqlx::Error> for StorageError {
+    fn from(err: sqlx::Error) -> Self {
+        let message = err.to_string();
+        match err {
+            sqlx::Error::PoolTimedOut => StorageError::Timeout(message),
+            sqlx::Error::Io(err) => StorageError::from(err),
+            sqlx::Error::Tls(_) | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed => {
+                StorageError::Unavailable(message)
+            }
+            sqlx::Error::Decode(_) | sqlx::Error::ColumnDecode { .. } => {
+                StorageError::Serialization(message)
+            }
+            sqlx::Error::Database(db) => classify_database_error(db.as_ref(), message),
+            _ => StorageError::Backend(message),
+        }
+    }
+}
+
+/// Classify a database error by its SQLSTATE (Postgres) or result code (SQLite).
+fn classify_database_error(db: &dyn sqlx::error::DatabaseError, message: String) -> StorageError {
+    if db.is_unique_violation() {
+        return StorageError::Conflict(message);
+    }
+
+    match db.code().as_deref() {
+        // Postgres: serialization_failure, deadlock_detected.
+        Some("40001") | Some("40P01") => StorageError::Conflict(message),
+        // Postgres: disk_full, out_of_memory.
+        Some("53100") | Some("53200") => StorageError::CapacityExceeded(message),
+        // Postgres: too_many_connections, admin_shutdown, crash_shutdown,
+        // cannot_connect_now.
+        Some("53300") | Some("57P01") | Some("57P02") | Some("57P03") => {
+            StorageError::Unavailable(message)
+        }
+        // SQLite reports extended result codes; the low byte is the primary code.
+        Some(code) => match code.parse::<i32>().map(|code| code & 0xff) {
+            // SQLITE_FULL
+            Ok(13) => StorageError::CapacityExceeded(message),
+            // SQLITE_BUSY, SQLITE_LOCKED
+            Ok(5) | Ok(6) => StorageError::Unavailable(message),
+            // SQLITE_CORRUPT, SQLITE_NOTADB
+            Ok(11) | Ok(26) => StorageError::Serialization(message),
+            _ => StorageError::Backend(message),
+        },
+        None => StorageError::Backend(message),
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let message = err.to_string();
+        match err.kind() {
+            ErrorKind::TimedOut => StorageError::Timeout(message),
+            ErrorKind::StorageFull => StorageError::CapacityExceeded(message),
+            ErrorKind::InvalidData => StorageError::Serialization(message),
+            ErrorKind::AlreadyExists => StorageError::Conflict(message),
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe => StorageError::Unavailable(message),
+            _ => StorageError::Backend(message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(err: serde_json::Error) -> Self {
+        StorageError::Serialization(err.to_string())
+    }
+}
+
+/// Abstraction over the underlying storage for secrets.
+///
+/// Implementors must guarantee that:
+///
+/// - consuming a view is atomic, so a secret is never served more times than
+///   it allows, however many readers race (see
+///   [`SecretStore::get_and_delete_secret`] and
+///   [`SecretStore::record_failed_pin`]);
+/// - tombstones, receipts and idempotency records are kept until their own
+///   `expires_at`, independently of the secret they describe, and an expired
+///   secret leaves its [`Secret::expiry_tombstone`] behind;
+/// - saving an idempotency record is put-if-absent, so racing retries agree on
+///   one secret;
+/// - [`SecretStore::is_durable`] only reports `true` if everything above
+///   survives a restart of the backend.
+#[async_trait]
+pub trait SecretStore: Send + Sync {
+    /// Persist a fully built `Secret` record and return it.
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret>;
+
+    /// Persist a new single-view secret and return the full `Secret` record,
+    /// including its id.
+    async fn store_secret(
+        &self,
+        ciphertext: String,
+        iv: String,
+        ttl_secs: u32,
+    ) -> StorageResult<Secret> {
+        self.insert_secret(Secret::new(ciphertext, iv, ttl_secs))
+            .await
+    }
+
+    /// Fetch a secret by id and use up one of its views, removing it from
+    /// storage once none remain.
+    ///
+    /// Must be atomic: however many readers race, a secret created with
+    /// `max_views = n` is returned at most `n` times. The returned record's
+    /// `remaining_views` reflects this read.
+    ///
+    /// Reading the last view leaves the secret's [`Secret::read_tombstone`] in
+    /// the same step, so readers that lost the race are told it is gone.
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>>;
+
+    /// Fetch a live secret by id without using up a view.
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>>;
+
+    /// Describe a live secret without using up a view.
+    ///
+    /// Backends that track expiry themselves should report their own idea of
+    /// `expires_at` rather than the one computed from the record.
+    async fn secret_metadata(&self, id: &str) -> StorageResult<Option<SecretMetadata>> {
+        Ok(self.peek_secret(id).await?.map(|secret| secret.metadata()))
+    }
+
+    /// Count a wrong PIN against a secret, burning it once `max_attempts`
+    /// wrong PINs have been entered.
+    ///
+    /// Returns the attempts left (zero once burned), or `None` if the secret
+    /// does not exist. Must be atomic so parallel guesses cannot exceed the limit.
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>>;
+
+    /// Delete a live secret outright, whatever views it has left.
+    ///
+    /// Returns `false` if there was no such secret.
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool>;
+
+    /// Change how long a live secret lives by replacing its `ttl_secs`, which
+    /// still counts from the same start. Callers keep the new expiry in the
+    /// future.
+    ///
+    /// Returns the updated secret, or `None` if there was no such secret.
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>>;
+
+    /// Leave `tombstone` for the secret `id`, replacing any earlier one. It is
+    /// kept until its `expires_at`.
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()>;
+
+    /// Fetch the unexpired tombstone left for the secret `id`, if any.
+    ///
+    /// Secrets that run out unread get no tombstone up front. Instead, a store
+    /// that drops an expired secret leaves its [`Secret::expiry_tombstone`]
+    /// behind, and reports that tombstone for an expired secret it still holds.
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>>;
+
+    /// Save `receipt` under its own id, replacing any earlier version. It is
+    /// kept until its `expires_at`, however long its secret lives.
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()>;
+
+    /// Fetch the unexpired receipt with id `id`, if any.
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>>;
+
+    /// Delete the receipt with id `id`, if there is one.
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()>;
+
+    /// Save `record` under the idempotency key `key`, unless an unexpired
+    /// record is already there, in which case that one is left alone and
+    /// returned. Must be atomic, so that racing retries agree on one secret.
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>>;
+
+    /// Fetch the unexpired record saved under the idempotency key `key`, if any.
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>>;
+
+    /// Overwrite the record saved under the idempotency key `key`, such as
+    /// when the secret it replays has been given a new expiry.
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()>;
+
+    /// Lightweight health check for the underlying backend.
+    async fn ping(&self) -> StorageResult<()>;
+
+    /// Whether secrets survive a restart of the backend, which long-lived
+    /// secrets depend on. Stores that keep secrets only in memory, or cannot
+    /// tell, report `false`.
+    async fn is_durable(&self) -> StorageResult<bool> {
+        Ok(false)
+    }
+
+    /// Connection pool usage, for backends that keep a pool of connections.
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+}
+
+/// Ids become file names and object keys, so anything outside the URL-safe
+/// base64 alphabet is rejected before it can be used to address another record.
+/// Every id and key the API generates passes.
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Snapshot of a connection pool's usage, suitable for health checks and metrics.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Upper bound on the number of connections the pool will open.
+    pub max_size: u32,
+    /// Connections currently open, whether idle or checked out.
+    pub connections: u32,
+    /// Open connections that are not currently checked out.
+    pub idle_connections: u32,
+    /// Total checkouts that had to wait for a connection to become available.
+    pub gets_waited: u64,
+    /// Total checkouts that gave up waiting for a connection.
+    pub gets_timed_out: u64,
+    /// Total connections opened over the lifetime of the pool.
+    pub connections_created: u64,
+    /// Total connections closed because they sat idle past the idle timeout.
+    pub connections_closed_idle_timeout: u64,
+}
+
+/// Upper bounds on how much an `InMemorySecretStore` may hold.
+///
+/// Both bounds cover tombstones, receipts and idempotency records as well as
+/// secrets. Sizes count the ciphertext and iv bytes of every secret, plus the
+/// key and serialized size of every other record.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InMemoryStoreLimits {
+    /// Maximum number of records of any kind held at once.
+    pub max_entries: Option<usize>,
+    /// Maximum combined size of all records held at once.
+    pub max_bytes: Option<usize>,
+}
+
+/// Simple in-memory implementation of `SecretStore` for tests and local development.
+///
+/// Expired secrets are never returned, and are evicted either when read or by a
+/// background sweeper started with [`InMemorySecretStore::spawn_sweeper`]. When
+/// [`InMemoryStoreLimits`] are configured, writes fail with
+/// [`StorageError::CapacityExceeded`] instead of growing without bound.
+#[derive(Debug, Default)]
+pub struct InMemorySecretStore {
+    inner: Arc<RwLock<InMemoryEntries>>,
+    limits: InMemoryStoreLimits,
+}
+
+#[derive(Debug, Default)]
+struct InMemoryEntries {
+    secrets: HashMap<String, Secret>,
+    bytes: usize,
+    tombstones: HashMap<String, Tombstone>,
+    receipts: HashMap<String, Receipt>,
+    idempotency_records: HashMap<String, IdempotencyRecord>,
+}
+
+impl InMemoryEntries {
+    fn insert(&mut self, secret: Secret) {
+        self.bytes += secret_size(&secret);
+        if let Some(previous) = self.secrets.insert(secret.id.clone(), secret) {
+            self.bytes -= secret_size(&previous);
+        }
+    }
+
+    fn remove(&mut self, id: &str) -> Option<Secret> {
+        let secret = self.secrets.remove(id)?;
+        self.bytes -= secret_size(&secret);
+        Some(secret)
+    }
+
+    /// Drop the secret `id` for having expired, leaving its tombstone behind.
+    fn expire(&mut self, id: &str) {
+        if let Some(tombstone) = self.remove(id).and_then(|secret| secret.expiry_tombstone()) {
+            self.put_tombstone(id, tombstone);
+        }
+    }
+
+    /// Drop every record that has expired at `now`, returning how many secrets were evicted.
+    fn purge_expired(&mut self, now: OffsetDateTime) -> usize {
+        let expired: Vec<String> = self
+            .secrets
+            .values()
+            .filter(|secret| secret.is_expired_at(now))
+            .map(|secret| secret.id.clone())
+            .collect();
+        for id in &expired {
+            self.expire(id);
+        }
+
+        self.tombstones
+            .retain(|_, tombstone| !tombstone.is_expired_at(now));
+        self.receipts
+            .retain(|_, receipt| !receipt.is_expired_at(now));
+        self.idempotency_records
+            .retain(|_, record| !record.is_expired_at(now));
+
+        self.bytes = self.secrets.values().map(secret_size).sum::<usize>()
+            + self
+                .tombstones
+                .iter()
+                .map(|(id, tombstone)| record_size(id, tombstone))
+                .sum::<usize>()
+            + self
+                .receipts
+                .iter()
+                .map(|(id, receipt)| record_size(id, receipt))
+                .sum::<usize>()
+            + self
+                .idempotency_records
+                .iter()
+                .map(|(key, record)| record_size(key, record))
+                .sum::<usize>();
+        expired.len()
+    }
+
+    fn len(&self) -> usize {
+        self.secrets.len()
+            + self.tombstones.len()
+            + self.receipts.len()
+            + self.idempotency_records.len()
+    }
+
+    /// Whether a record of `size` bytes fits, given the size of the record it
+    /// replaces, if any. Replacing a record never needs a new entry.
+    fn has_room_for(
+        &self,
+        limits: &InMemoryStoreLimits,
+        replaced: Option<usize>,
+        size: usize,
+    ) -> bool {
+        let entries_ok =
+            replaced.is_some() || limits.max_entries.is_none_or(|max| self.len() < max);
+        let bytes_ok = limits
+            .max_bytes
+            .is_none_or(|max| self.bytes - replaced.unwrap_or(0) + size <= max);
+        entries_ok && bytes_ok
+    }
+
+    /// Check there is room for a record, reclaiming expired records first if
+    /// there is not. `replaced` reports the size of the record it would replace.
+    fn make_room(
+        &mut self,
+        limits: &InMemoryStoreLimits,
+        replaced: impl Fn(&Self) -> Option<usize>,
+        size: usize,
+    ) -> StorageResult<()> {
+        if self.has_room_for(limits, replaced(self), size) {
+            return Ok(());
+        }
+
+        // Expired records should never keep live ones out, so reclaim them
+        // before deciding the store is really full.
+        self.purge_expired(OffsetDateTime::now_utc());
+        if self.has_room_for(limits, replaced(self), size) {
+            Ok(())
+        } else {
+            Err(StorageError::CapacityExceeded(
+                "in-memory secret store is full".to_string(),
+            ))
+        }
+    }
+
+    fn put_tombstone(&mut self, id: &str, tombstone: Tombstone) {
+        self.bytes += record_size(id, &tombstone);
+        if let Some(previous) = self.tombstones.insert(id.to_string(), tombstone) {
+            self.bytes -= record_size(id, &previous);
+        }
+    }
+
+    fn put_receipt(&mut self, receipt: Receipt) {
+        self.bytes += record_size(&receipt.id, &receipt);
+        if let Some(previous) = self.receipts.insert(receipt.id.clone(), receipt) {
+            self.bytes -= record_size(&previous.id, &previous);
+        }
+    }
+
+    fn remove_receipt(&mut self, id: &str) {
+        if let Some(receipt) = self.receipts.remove(id) {
+            self.bytes -= record_size(id, &receipt);
+        }
+    }
+
+    fn put_idempotency_record(&mut self, key: &str, record: IdempotencyRecord) {
+        self.bytes += record_size(key, &record);
+        if let Some(previous) = self.idempotency_records.insert(key.to_string(), record) {
+            self.bytes -= record_size(key, &previous);
+        }
+    }
+}
+
+fn secret_size(secret: &Secret) -> usize {
+    secret.ciphertext.len() + secret.iv.len()
+}
+
+/// Approximate memory held by a tombstone, receipt or idempotency record.
+fn record_size<T: Serialize>(key: &str, record: &T) -> usize {
+    key.len() + serde_json::to_string(record).map_or(0, |json| json.len())
+}
+
+impl InMemorySecretStore {
+    pub fn new() -> Self {
+        Self::with_limits(InMemoryStoreLimits::default())
+    }
+
+    /// Construct an in-memory store that refuses new secrets past the given limits.
+    pub fn with_limits(limits: InMemoryStoreLimits) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(InMemoryEntries::default())),
+            limits,
+        }
+    }
+
+    /// Start a background task that evicts expired secrets every `interval`.
+    ///
+    /// The task only holds a weak reference to the store and exits once the
+    /// store has been dropped.
+    pub fn spawn_sweeper(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; there is nothing to sweep yet.
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+
+                let evicted = inner.write().await.purge_expired(OffsetDateTime::now_utc());
+                if evicted > 0 {
+                    tracing::debug!(evicted, "swept expired secrets from in-memory store");
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl SecretStore for InMemorySecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        let size = secret_size(&secret);
+
+        let mut guard = self.inner.write().await;
+        guard.make_room(
+            &self.limits,
+            |entries| entries.secrets.get(&secret.id).map(secret_size),
+            size,
+        )?;
+        guard.insert(secret.clone());
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let mut guard = self.inner.write().await;
+        let Some(secret) = guard.secrets.get_mut(id) else {
+            return Ok(None);
+        };
+
+        let now = OffsetDateTime::now_utc();
+        if secret.is_expired_at(now) {
+            guard.expire(id);
+            return Ok(None);
+        }
+
+        // The write lock makes the decrement and the removal one atomic step.
+        if secret.consume_view(now) {
+            let secret = guard.remove(id);
+            if let Some(tombstone) = secret.as_ref().and_then(Secret::read_tombstone) {
+                guard.put_tombstone(id, tombstone);
+            }
+            Ok(secret)
+        } else {
+            Ok(Some(secret.clone()))
+        }
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let guard = self.inner.read().await;
+        let now = OffsetDateTime::now_utc();
+
+        Ok(guard
+            .secrets
+            .get(id)
+            .filter(|secret| !secret.is_expired_at(now))
+            .cloned())
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        let mut guard = self.inner.write().await;
+        let Some(secret) = guard.secrets.get_mut(id) else {
+            return Ok(None);
+        };
+
+        if secret.is_expired_at(OffsetDateTime::now_utc()) {
+            guard.expire(id);
+            return Ok(None);
+        }
+
+        let attempts_left = secret.record_failed_pin(max_attempts);
+        if attempts_left == 0 {
+            guard.remove(id);
+        }
+        Ok(Some(attempts_left))
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        let mut guard = self.inner.write().await;
+        let now = OffsetDateTime::now_utc();
+
+        match guard.secrets.get(id) {
+            Some(secret) if secret.is_expired_at(now) => {
+                guard.expire(id);
+                Ok(false)
+            }
+            Some(_) => Ok(guard.remove(id).is_some()),
+            None => Ok(false),
+        }
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        let mut guard = self.inner.write().await;
+        let now = OffsetDateTime::now_utc();
+
+        Ok(guard
+            .secrets
+            .get_mut(id)
+            .filter(|secret| !secret.is_expired_at(now))
+            .map(|secret| {
+                secret.ttl_secs = ttl_secs;
+                secret.clone()
+            }))
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        let mut guard = self.inner.write().await;
+        guard.make_room(
+            &self.limits,
+            |entries| {
+                entries
+                    .tombstones
+                    .get(id)
+                    .map(|previous| record_size(id, previous))
+            },
+            record_size(id, tombstone),
+        )?;
+        guard.put_tombstone(id, tombstone.clone());
+        Ok(())
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        let guard = self.inner.read().await;
+        let now = OffsetDateTime::now_utc();
+
+        let tombstone = guard.tombstones.get(id).cloned().or_else(|| {
+            guard
+                .secrets
+                .get(id)
+                .filter(|secret| secret.is_expired_at(now))
+                .and_then(Secret::expiry_tombstone)
+        });
+        Ok(tombstone.filter(|tombstone| !tombstone.is_expired_at(now)))
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        let mut guard = self.inner.write().await;
+        guard.make_room(
+            &self.limits,
+            |entries| {
+                entries
+                    .receipts
+                    .get(&receipt.id)
+                    .map(|previous| record_size(&previous.id, previous))
+            },
+            record_size(&receipt.id, receipt),
+        )?;
+        guard.put_receipt(receipt.clone());
+        Ok(())
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        let guard = self.inner.read().await;
+        let now = OffsetDateTime::now_utc();
+
+        Ok(guard
+            .receipts
+            .get(id)
+            .filter(|receipt| !receipt.is_expired_at(now))
+            .cloned())
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        self.inner.write().await.remove_receipt(id);
+        Ok(())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        let mut guard = self.inner.write().await;
+        let now = OffsetDateTime::now_utc();
+
+        if let Some(existing) = guard.idempotency_records.get(key) {
+            if !existing.is_expired_at(now) {
+                return Ok(Some(existing.clone()));
+            }
+        }
+        guard.make_room(
+            &self.limits,
+            |entries| {
+                entries
+                    .idempotency_records
+                    .get(key)
+                    .map(|previous| record_size(key, previous))
+            },
+            record_size(key, record),
+        )?;
+        guard.put_idempotency_record(key, record.clone());
+        Ok(None)
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        let guard = self.inner.read().await;
+        let now = OffsetDateTime::now_utc();
+
+        Ok(guard
+            .idempotency_records
+            .get(key)
+            .filter(|record| !record.is_expired_at(now))
+            .cloned())
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        let mut guard = self.inner.write().await;
+        guard.make_room(
+            &self.limits,
+            |entries| {
+                entries
+                    .idempotency_records
+                    .get(key)
+                    .map(|previous| record_size(key, previous))
+            },
+            record_size(key, record),
+        )?;
+        guard.put_idempotency_record(key, record.clone());
+        Ok(())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        // For the in-memory implementation there is nothing to verify beyond being constructed.
+        Ok(())
+    }
+}
+
+/// Summarise an sqlx pool in the shape shared with the other backends.
+///
+/// sqlx does not track checkout statistics, so only the gauges are filled in.
+fn sqlx_pool_stats<DB: sqlx::Database>(pool: &sqlx::Pool<DB>) -> PoolStats {
+    PoolStats {
+        max_size: pool.options().get_max_connections(),
+        connections: pool.size(),
+        idle_connections: pool.num_idle() as u32,
+        gets_waited: 0,
+        gets_timed_out: 0,
+        connections_created: 0,
+        connections_closed_idle_timeout: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TombstoneReason;
+
+    #[tokio::test]
+    async fn in_memory_store_respects_ttl_on_read() {
+        let store = InMemorySecretStore::new();
+        let secret = store
+            .store_secret("ciphertext".into(), "iv".into(), 1)
+            .await
+            .expect("store_secret should succeed");
+
+        {
+            let mut guard = store.inner.write().await;
+            let entry = guard
+                .secrets
+                .get_mut(&secret.id)
+                .expect("secret should be present in store");
+            entry.created_at = OffsetDateTime::UNIX_EPOCH;
+        }
+
+        let result = store
+            .get_and_delete_secret(&secret.id)
+            .await
+            .expect("get must succeed");
+
+        assert!(
+            result.is_none(),
+            "secret should be treated as expired when TTL elapsed"
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_returns_secret_once_when_not_expired() {
+        let store = InMemorySecretStore::new();
+        let secret = store
+            .store_secret("ciphertext".into(), "iv".into(), 3600)
+            .await
+            .expect("store_secret should succeed");
+
+        let first = store
+            .get_and_delete_secret(&secret.id)
+            .await
+            .expect("first read must succeed");
+        assert!(first.is_some(), "first read should return the secret");
+
+        let second = store
+            .get_and_delete_secret(&secret.id)
+            .await
+            .expect("second read must succeed");
+        assert!(
+            second.is_none(),
+            "secret should be removed after first successful read"
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_rejects_secrets_past_entry_limit() {
+        let store = InMemorySecretStore::with_limits(InMemoryStoreLimits {
+            max_entries: Some(1),
+            max_bytes: None,
+        });
+
+        store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect("first secret should fit");
+
+        let err = store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect_err("second secret should exceed max_entries");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_counts_side_records_against_limits() {
+        let store = InMemorySecretStore::with_limits(InMemoryStoreLimits {
+            max_entries: Some(2),
+            max_bytes: None,
+        });
+        let secret = store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect("first secret should fit");
+        let expires_at = secret.expires_at();
+
+        let tombstone = Tombstone::new(TombstoneReason::Revoked, expires_at);
+        store
+            .put_tombstone("gone", &tombstone)
+            .await
+            .expect("tombstone should fit");
+        store
+            .put_tombstone("gone", &tombstone)
+            .await
+            .expect("replacing a tombstone should not need a new entry");
+
+        let receipt = Receipt::new("receipt".into(), &secret, expires_at);
+        let err = store
+            .put_receipt(&receipt)
+            .await
+            .expect_err("receipt should exceed max_entries");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+
+        let record = IdempotencyRecord {
+            request_hash: "hash".into(),
+            secret_id: secret.id.clone(),
+            sealed_management_token: "sealed-token".into(),
+            receipt_id: None,
+            secret_expires_at: expires_at,
+            expires_at,
+        };
+        let err = store
+            .put_idempotency_record("key", &record)
+            .await
+            .expect_err("idempotency record should exceed max_entries");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+
+        let err = store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect_err("side records should count towards max_entries");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+
+        // The byte cap covers side records too.
+        let store = InMemorySecretStore::with_limits(InMemoryStoreLimits {
+            max_entries: None,
+            max_bytes: Some(record_size("receipt", &receipt)),
+        });
+        store
+            .put_receipt(&receipt)
+            .await
+            .expect("receipt within max_bytes should fit");
+        let err = store
+            .put_tombstone("gone", &tombstone)
+            .await
+            .expect_err("tombstone past max_bytes should be rejected");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+        assert_eq!(
+            store.inner.read().await.bytes,
+            record_size("receipt", &receipt)
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_rejects_secrets_past_byte_limit() {
+        let store = InMemorySecretStore::with_limits(InMemoryStoreLimits {
+            max_entries: None,
+            max_bytes: Some(16),
+        });
+
+        let err = store
+            .store_secret("a-much-too-long-ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect_err("secret larger than max_bytes should be rejected");
+        assert!(matches!(err, StorageError::CapacityExceeded(_)));
+
+        store
+            .store_secret("short".into(), "iv".into(), 60)
+            .await
+            .expect("secret within max_bytes should fit");
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_reclaims_expired_entries_when_full() {
+        let store = InMemorySecretStore::with_limits(InMemoryStoreLimits {
+            max_entries: Some(1),
+            max_bytes: None,
+        });
+
+        let stale = store
+            .store_secret("ciphertext".into(), "iv".into(), 1)
+            .await
+            .expect("store_secret should succeed");
+        store
+            .inner
+            .write()
+            .await
+            .secrets
+            .get_mut(&stale.id)
+            .expect("secret should be present in store")
+            .created_at = OffsetDateTime::UNIX_EPOCH;
+
+        store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect("expired entries should not count against the limit");
+    }
+
+    #[tokio::test]
+    async fn sweeper_evicts_expired_secrets_that_are_never_read() {
+        let store = InMemorySecretStore::new();
+        let expired = store
+            .store_secret("ciphertext".into(), "iv".into(), 1)
+            .await
+            .expect("store_secret should succeed");
+        let live = store
+            .store_secret("ciphertext".into(), "iv".into(), 3600)
+            .await
+            .expect("store_secret should succeed");
+
+        store
+            .inner
+            .write()
+            .await
+            .secrets
+            .get_mut(&expired.id)
+            .expect("secret should be present in store")
+            .created_at = OffsetDateTime::UNIX_EPOCH;
+
+        let sweeper = store.spawn_sweeper(Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        {
+            let guard = store.inner.read().await;
+            assert!(!guard.secrets.contains_key(&expired.id));
+            assert!(guard.secrets.contains_key(&live.id));
+            assert_eq!(guard.bytes, secret_size(&live));
+        }
+
+        drop(store);
+        tokio::time::timeout(Duration::from_secs(1), sweeper)
+            .await
+            .expect("sweeper should stop once the store is dropped")
+            .expect("sweeper task should not panic");
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_reports_expiry_tombstones_for_expired_secrets() {
+        let store = InMemorySecretStore::new();
+        let secret = store
+            .insert_secret(Secret::new("c".into(), "i".into(), 60).with_tombstone_secs(300))
+            .await
+            .expect("insert_secret should succeed");
+        assert_eq!(store.get_tombstone(&secret.id).await.unwrap(), None);
+
+        store
+            .inner
+            .write()
+            .await
+            .secrets
+            .get_mut(&secret.id)
+            .expect("secret should be present in store")
+            .created_at -= time::Duration::seconds(120);
+
+        let tombstone = store
+            .get_tombstone(&secret.id)
+            .await
+            .unwrap()
+            .expect("an expired secret should report its tombstone");
+        assert_eq!(tombstone.reason, TombstoneReason::Expired);
+
+        assert!(!store.burn_secret(&secret.id).await.unwrap());
+        assert_eq!(
+            store.inner.read().await.tombstones.get(&secret.id),
+            Some(&tombstone),
+            "dropping the expired secret should leave the same tombstone"
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_counts_down_views() {
+        let store = InMemorySecretStore::new();
+        let secret = store
+            .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(2))
+            .await
+            .unwrap();
+
+        let first = store.get_and_delete_secret(&secret.id).await.unwrap();
+        assert_eq!(first.map(|s| s.remaining_views), Some(1));
+        assert_eq!(store.inner.read().await.secrets.len(), 1);
+
+        let second = store.get_and_delete_secret(&secret.id).await.unwrap();
+        assert_eq!(second.map(|s| s.remaining_views), Some(0));
+        assert!(store.inner.read().await.secrets.is_empty());
+        assert_eq!(store.inner.read().await.bytes, 0);
+
+        assert!(
+            store
+                .get_and_delete_secret(&secret.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_burns_secret_after_last_wrong_pin() {
+        let store = InMemorySecretStore::new();
+        let secret = store
+            .insert_secret(
+                Secret::new("ciphertext".into(), "iv".into(), 60).with_pin_hash("h".into()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.record_failed_pin(&secret.id, 2).await.unwrap(),
+            Some(1)
+        );
+        assert!(store.peek_secret(&secret.id).await.unwrap().is_some());
+        assert_eq!(
+            store.record_failed_pin(&secret.id, 2).await.unwrap(),
+            Some(0)
+        );
+        assert!(store.peek_secret(&secret.id).await.unwrap().is_none());
+        assert_eq!(store.record_failed_pin(&secret.id, 2).await.unwrap(), None);
+    }
+
+    #[test]
+    fn redis_errors_are_classified() {
+        use ::redis::{ErrorKind, RedisError};
+
+        let loading = RedisError::from((ErrorKind::BusyLoadingError, "loading dataset"));
+        assert!(matches!(
+            StorageError::from(loading),
+            StorageError::Unavailable(_)
+        ));
+
+        let refused = RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        assert!(matches!(
+            StorageError::from(refused),
+            StorageError::Unavailable(_)
+        ));
+
+        let timeout = RedisError::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert!(matches!(
+            StorageError::from(timeout),
+            StorageError::Timeout(_)
+        ));
+
+        let oom = ::redis::make_extension_error(
+            "OOM".to_string(),
+            Some("command not allowed when used memory > 'maxmemory'".to_string()),
+        );
+        assert!(matches!(
+            StorageError::from(oom),
+            StorageError::CapacityExceeded(_)
+        ));
+
+        let wrong_type = RedisError::from((ErrorKind::TypeError, "unexpected response"));
+        assert!(matches!(
+            StorageError::from(wrong_type),
+            StorageError::Serialization(_)
+        ));
+
+        let auth = RedisError::from((ErrorKind::AuthenticationFailed, "WRONGPASS"));
+        assert!(matches!(StorageError::from(auth), StorageError::Config(_)));
+    }
+
+    #[test]
+    fn io_and_serde_errors_are_classified() {
+        use std::io::{Error, ErrorKind};
+
+        let full = Error::from(ErrorKind::StorageFull);
+        assert!(matches!(
+            StorageError::from(full),
+            StorageError::CapacityExceeded(_)
+        ));
+
+        let timed_out = Error::from(ErrorKind::TimedOut);
+        assert!(matches!(
+            StorageError::from(timed_out),
+            StorageError::Timeout(_)
+        ));
+
+        let denied = Error::from(ErrorKind::PermissionDenied);
+        assert!(matches!(
+            StorageError::from(denied),
+            StorageError::Backend(_)
+        ));
+
+        let corrupt = serde_json::from_str::<Secret>("{not json").unwrap_err();
+        assert!(matches!(
+            StorageError::from(corrupt),
+            StorageError::Serialization(_)
+        ));
+    }
+}
@@ -0,0 +1,443 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::postgres::{PgExecutor, PgPool, PgPoolOptions};
+use time::OffsetDateTime;
+
+use super::{PoolStats, SecretStore, StorageError, StorageResult, sqlx_pool_stats};
+use crate::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+
+/// PostgreSQL-backed implementation of `SecretStore`.
+///
+/// Each secret is a row holding its JSON-serialized `Secret` and expiry time.
+/// Reading the last (or only) view is a single `DELETE ... RETURNING` that
+/// checks expiry; earlier views of a multi-view secret are taken with an
+/// `UPDATE ... RETURNING` instead. Either statement locks the row, so
+/// concurrent readers queue on it and the one-time guarantee holds no matter
+/// how many replicas share the database.
+/// Expired rows that are never read are removed by the sweeper started with
+/// [`PostgresSecretStore::spawn_sweeper`].
+pub struct PostgresSecretStore {
+    /// Shared with the sweeper only through a weak reference, so dropping the
+    /// store closes the pool and stops the sweeper.
+    pool: Arc<PgPool>,
+}
+
+impl PostgresSecretStore {
+    /// Connect to the database at `url` and run pending schema migrations.
+    pub async fn new(url: &str) -> StorageResult<Self> {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_secs(5))
+            .connect(url)
+            .await?;
+
+        // Migrations take an advisory lock, so replicas starting together are safe.
+        sqlx::migrate!("./migrations/postgres")
+            .run(&pool)
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    /// Start a background task that deletes expired rows every `interval`.
+    ///
+    /// The task only holds a weak reference to the store's connection pool and
+    /// exits once the store has been dropped.
+    pub fn spawn_sweeper(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::downgrade(&self.pool);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+
+                match purge_expired(&pool, OffsetDateTime::now_utc()).await {
+                    Ok(0) => {}
+                    Ok(purged) => {
+                        tracing::debug!(purged, "swept expired secrets from Postgres store")
+                    }
+                    Err(err) => tracing::warn!("failed to sweep Postgres store: {:?}", err),
+                }
+            }
+        })
+    }
+}
+
+async fn purge_expired(pool: &PgPool, now: OffsetDateTime) -> StorageResult<u64> {
+    // Secrets that ran out unread leave their tombstone in the same step.
+    let mut tx = pool.begin().await?;
+    let expired: Vec<(String, String)> =
+        sqlx::query_as("DELETE FROM secrets WHERE expires_at <= $1 RETURNING id, body")
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await?;
+    for (id, body) in &expired {
+        // A record that cannot be decoded could never be served either.
+        let Ok(secret) = serde_json::from_str::<Secret>(body) else {
+            continue;
+        };
+        if let Some(tombstone) = secret.expiry_tombstone() {
+            if !tombstone.is_expired_at(now) {
+                write_tombstone(&mut *tx, id, &tombstone).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    let tombstones = sqlx::query("DELETE FROM tombstones WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    let receipts = sqlx::query("DELETE FROM receipts WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    let idempotency_records = sqlx::query("DELETE FROM idempotency_records WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    Ok(expired.len() as u64
+        + tombstones.rows_affected()
+        + receipts.rows_affected()
+        + idempotency_records.rows_affected())
+}
+
+/// Save `tombstone` for the secret `id`, replacing any earlier one.
+async fn write_tombstone<'e>(
+    executor: impl PgExecutor<'e>,
+    id: &str,
+    tombstone: &Tombstone,
+) -> StorageResult<()> {
+    let body = serde_json::to_string(tombstone)?;
+
+    sqlx::query(
+        "INSERT INTO tombstones (id, expires_at, body) VALUES ($1, $2, $3) \
+         ON CONFLICT (id) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+    )
+    .bind(id)
+    .bind(tombstone.expires_at)
+    .bind(body)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl SecretStore for PostgresSecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        let body = serde_json::to_string(&secret)?;
+
+        sqlx::query(
+            "INSERT INTO secrets (id, expires_at, body, remaining_views) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&secret.id)
+        .bind(secret.expires_at())
+        .bind(body)
+        .bind(secret.remaining_views as i32)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+
+        // A reader that waited on the row lock re-checks the row it finds, so
+        // it may miss both statements when a concurrent read took the
+        // second-to-last view. Views only ever go down, so the next DELETE
+        // then settles it.
+        for _ in 0..2 {
+            let body: Option<String> = sqlx::query_scalar(
+                "DELETE FROM secrets \
+                 WHERE id = $1 AND expires_at > $2 AND remaining_views <= 1 \
+                 RETURNING body",
+            )
+            .bind(id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some(body) = body {
+                let mut secret: Secret = serde_json::from_str(&body)?;
+                secret.mark_read(now);
+                secret.remaining_views = 0;
+                // In the same transaction, so losing readers find it.
+                if let Some(tombstone) = secret.read_tombstone() {
+                    write_tombstone(&mut *tx, id, &tombstone).await?;
+                }
+                tx.commit().await?;
+                return Ok(Some(secret));
+            }
+
+            let row: Option<(String, i32)> = sqlx::query_as(
+                "UPDATE secrets SET remaining_views = remaining_views - 1 \
+                 WHERE id = $1 AND expires_at > $2 AND remaining_views > 1 \
+                 RETURNING body, remaining_views",
+            )
+            .bind(id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some((body, remaining_views)) = row {
+                tx.commit().await?;
+                let mut secret: Secret = serde_json::from_str(&body)?;
+                secret.mark_read(now);
+                secret.remaining_views = remaining_views as u32;
+                return Ok(Some(secret));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let now = OffsetDateTime::now_utc();
+
+        let row: Option<(String, i32, i32)> = sqlx::query_as(
+            "SELECT body, remaining_views, failed_pin_attempts FROM secrets \
+             WHERE id = $1 AND expires_at > $2",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        let Some((body, remaining_views, failed_pin_attempts)) = row else {
+            return Ok(None);
+        };
+
+        let mut secret: Secret = serde_json::from_str(&body)?;
+        secret.remaining_views = remaining_views.max(0) as u32;
+        secret.failed_pin_attempts = failed_pin_attempts.max(0) as u32;
+        Ok(Some(secret))
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+
+        let failures: Option<i32> = sqlx::query_scalar(
+            "UPDATE secrets SET failed_pin_attempts = failed_pin_attempts + 1 \
+             WHERE id = $1 AND expires_at > $2 \
+             RETURNING failed_pin_attempts",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(failures) = failures else {
+            return Ok(None);
+        };
+
+        let attempts_left = max_attempts.saturating_sub(failures.max(0) as u32);
+        if attempts_left == 0 {
+            sqlx::query("DELETE FROM secrets WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(Some(attempts_left))
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        let result = sqlx::query("DELETE FROM secrets WHERE id = $1 AND expires_at > $2")
+            .bind(id)
+            .bind(OffsetDateTime::now_utc())
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        let Some(mut secret) = self.peek_secret(id).await? else {
+            return Ok(None);
+        };
+        secret.ttl_secs = ttl_secs;
+
+        // Only the view and PIN counters change after creation, and they live
+        // in their own columns, so rewriting the body cannot lose an update.
+        // A row deleted since the peek is simply not matched.
+        let row: Option<(i32, i32)> = sqlx::query_as(
+            "UPDATE secrets SET body = $1, expires_at = $2 \
+             WHERE id = $3 AND expires_at > $4 \
+             RETURNING remaining_views, failed_pin_attempts",
+        )
+        .bind(serde_json::to_string(&secret)?)
+        .bind(secret.expires_at())
+        .bind(id)
+        .bind(OffsetDateTime::now_utc())
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(row.map(|(remaining_views, failed_pin_attempts)| {
+            secret.remaining_views = remaining_views.max(0) as u32;
+            secret.failed_pin_attempts = failed_pin_attempts.max(0) as u32;
+            secret
+        }))
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        write_tombstone(&*self.pool, id, tombstone).await
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        let now = OffsetDateTime::now_utc();
+
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM tombstones WHERE id = $1 AND expires_at > $2")
+                .bind(id)
+                .bind(now)
+                .fetch_optional(&*self.pool)
+                .await?;
+        if let Some(body) = body {
+            return Ok(Some(serde_json::from_str(&body)?));
+        }
+
+        // Expired secrets stay in the table until the sweeper gets to them.
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM secrets WHERE id = $1 AND expires_at <= $2")
+                .bind(id)
+                .bind(now)
+                .fetch_optional(&*self.pool)
+                .await?;
+        let secret: Option<Secret> = body.map(|body| serde_json::from_str(&body)).transpose()?;
+
+        Ok(secret
+            .and_then(|secret| secret.expiry_tombstone())
+            .filter(|tombstone| !tombstone.is_expired_at(now)))
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        let body = serde_json::to_string(receipt)?;
+
+        sqlx::query(
+            "INSERT INTO receipts (id, expires_at, body) VALUES ($1, $2, $3) \
+             ON CONFLICT (id) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+        )
+        .bind(&receipt.id)
+        .bind(receipt.expires_at)
+        .bind(body)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM receipts WHERE id = $1 AND expires_at > $2")
+                .bind(id)
+                .bind(OffsetDateTime::now_utc())
+                .fetch_optional(&*self.pool)
+                .await?;
+
+        Ok(body.map(|body| serde_json::from_str(&body)).transpose()?)
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        sqlx::query("DELETE FROM receipts WHERE id = $1")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        let body = serde_json::to_string(record)?;
+
+        // An expired record may be replaced; a live one only ever reported.
+        // Should the live one vanish between the two statements, try again.
+        loop {
+            let inserted = sqlx::query(
+                "INSERT INTO idempotency_records (key, expires_at, body) VALUES ($1, $2, $3) \
+                 ON CONFLICT (key) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body \
+                 WHERE idempotency_records.expires_at <= $4",
+            )
+            .bind(key)
+            .bind(record.expires_at)
+            .bind(&body)
+            .bind(OffsetDateTime::now_utc())
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+            if inserted > 0 {
+                return Ok(None);
+            }
+
+            if let Some(existing) = self.get_idempotency_record(key).await? {
+                return Ok(Some(existing));
+            }
+        }
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        let body: Option<String> = sqlx::query_scalar(
+            "SELECT body FROM idempotency_records WHERE key = $1 AND expires_at > $2",
+        )
+        .bind(key)
+        .bind(OffsetDateTime::now_utc())
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(body.map(|body| serde_json::from_str(&body)).transpose()?)
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        let body = serde_json::to_string(record)?;
+
+        sqlx::query(
+            "INSERT INTO idempotency_records (key, expires_at, body) VALUES ($1, $2, $3) \
+             ON CONFLICT (key) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+        )
+        .bind(key)
+        .bind(record.expires_at)
+        .bind(body)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        sqlx::query("SELECT 1").execute(&*self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn is_durable(&self) -> StorageResult<bool> {
+        Ok(true)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(sqlx_pool_stats(&*self.pool))
+    }
+}
@@ -0,0 +1,1158 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bb8::{ManageConnection, Pool, PooledConnection, RunError};
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{SentinelClient, SentinelClientBuilder, SentinelServerType};
+use redis::{
+    ClientTlsConfig, Cmd, ConnectionAddr, IntoConnectionInfo, Pipeline, RedisConnectionInfo,
+    RedisFuture, RedisResult, Role, Script, TlsCertificates, TlsMode, Value,
+};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+
+use super::{PoolStats, SecretStore, StorageError, StorageResult, is_valid_id};
+use crate::models::{
+    IdempotencyRecord, Receipt, Secret, SecretMetadata, Tombstone, TombstoneReason,
+};
+
+/// Connection pool settings for `RedisSecretStore`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedisPoolConfig {
+    /// Maximum number of connections held by the pool.
+    pub max_size: u32,
+    /// Number of idle connections the pool tries to keep open, if any.
+    pub min_idle: Option<u32>,
+    /// How long a connection may sit idle before it is closed.
+    pub idle_timeout: Option<Duration>,
+    /// How long a request waits for a free connection before failing.
+    pub connection_timeout: Duration,
+}
+
+impl Default for RedisPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 16,
+            min_idle: None,
+            idle_timeout: Some(Duration::from_secs(300)),
+            connection_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// How `RedisSecretStore` reaches Redis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RedisTopology {
+    /// A single Redis server, e.g. `redis://localhost:6379/0`.
+    Standalone { url: String },
+    /// A master located through Redis Sentinel.
+    ///
+    /// Sentinels are given as `redis://host:port` URLs; `db` selects the
+    /// logical database on whichever node is currently master.
+    Sentinel {
+        sentinels: Vec<String>,
+        master_name: String,
+        db: i64,
+    },
+    /// A Redis Cluster, discovered from one or more seed node URLs.
+    Cluster { nodes: Vec<String> },
+}
+
+/// Credentials and TLS material applied to every Redis node the store talks to.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RedisSecurity {
+    /// ACL username; `None` authenticates as the `default` user.
+    pub username: Option<String>,
+    /// ACL (or legacy `requirepass`) password.
+    pub password: Option<String>,
+    /// Connect over TLS. Standalone URLs can also opt in with the `rediss://` scheme.
+    pub tls: bool,
+    /// PEM CA bundle to trust instead of the system roots.
+    pub ca_cert: Option<Vec<u8>>,
+    /// PEM client certificate and private key, for mutual TLS.
+    pub client_cert: Option<RedisClientCert>,
+}
+
+/// PEM client certificate and private key presented to Redis for mutual TLS.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RedisClientCert {
+    pub cert: Vec<u8>,
+    pub key: Vec<u8>,
+}
+
+impl RedisSecurity {
+    fn has_certificates(&self) -> bool {
+        self.ca_cert.is_some() || self.client_cert.is_some()
+    }
+
+    /// Check that the PEM material actually parses. The Redis client silently
+    /// skips anything it cannot read, which would otherwise surface much later
+    /// as an opaque handshake failure.
+    fn validate_certificates(&self) -> StorageResult<()> {
+        if let Some(ca_cert) = &self.ca_cert {
+            let certs = CertificateDer::pem_slice_iter(ca_cert)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| StorageError::Config(format!("invalid Redis CA bundle: {err}")))?;
+            if certs.is_empty() {
+                return Err(StorageError::Config(
+                    "Redis CA bundle contains no PEM certificates".to_string(),
+                ));
+            }
+        }
+
+        if let Some(client) = &self.client_cert {
+            let certs = CertificateDer::pem_slice_iter(&client.cert)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| {
+                    StorageError::Config(format!("invalid Redis client certificate: {err}"))
+                })?;
+            if certs.is_empty() {
+                return Err(StorageError::Config(
+                    "Redis client certificate file contains no PEM certificates".to_string(),
+                ));
+            }
+            PrivateKeyDer::from_pem_slice(&client.key).map_err(|err| {
+                StorageError::Config(format!("invalid Redis client private key: {err}"))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn certificates(&self) -> TlsCertificates {
+        TlsCertificates {
+            client_tls: self.client_cert.as_ref().map(|client| ClientTlsConfig {
+                client_cert: client.cert.clone(),
+                client_key: client.key.clone(),
+            }),
+            root_cert: self.ca_cert.clone(),
+        }
+    }
+}
+
+// Hand-written so credentials and key material never end up in logs.
+impl std::fmt::Debug for RedisSecurity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisSecurity")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("tls", &self.tls)
+            .field("ca_cert", &self.ca_cert.as_ref().map(|_| "<pem>"))
+            .field("client_cert", &self.client_cert.as_ref().map(|_| "<pem>"))
+            .finish()
+    }
+}
+
+/// bb8 connection manager that knows how to reach each `RedisTopology`.
+enum RedisConnector {
+    Standalone(redis::Client),
+    // Resolving the master needs `&mut`, and only happens when the pool opens
+    // a new connection, so a lock here never sits on the request path.
+    Sentinel(Mutex<SentinelClient>),
+    Cluster(ClusterClient),
+}
+
+impl RedisConnector {
+    /// Build a connector without connecting yet.
+    ///
+    /// Every error here is a configuration mistake (bad URL, unusable
+    /// certificates, TLS material for a plaintext connection), so they are all
+    /// reported as [`StorageError::Config`].
+    fn new(topology: RedisTopology, security: &RedisSecurity) -> StorageResult<Self> {
+        if security.has_certificates() && !uses_tls(&topology, security) {
+            return Err(StorageError::Config(
+                "TLS certificates were provided but the Redis connection is not TLS; \
+                 use a rediss:// URL or enable TLS"
+                    .to_string(),
+            ));
+        }
+        security.validate_certificates()?;
+        if security.username.is_some() && security.password.is_none() {
+            return Err(StorageError::Config(
+                "a Redis ACL username was provided without a password".to_string(),
+            ));
+        }
+
+        let connector = match topology {
+            RedisTopology::Standalone { url } => {
+                let mut info = url.as_str().into_connection_info().map_err(config_error)?;
+                apply_credentials(&mut info.redis, security);
+
+                if security.tls {
+                    if let ConnectionAddr::Tcp(host, port) = info.addr {
+                        info.addr = ConnectionAddr::TcpTls {
+                            host,
+                            port,
+                            insecure: false,
+                            tls_params: None,
+                        };
+                    }
+                }
+
+                let client = if security.has_certificates() {
+                    redis::Client::build_with_tls(info, security.certificates())
+                } else {
+                    redis::Client::open(info)
+                };
+                Self::Standalone(client.map_err(config_error)?)
+            }
+            RedisTopology::Sentinel {
+                sentinels,
+                master_name,
+                db,
+            } => {
+                let addrs = sentinels
+                    .iter()
+                    .map(|url| url.as_str().into_connection_info().map(|info| info.addr))
+                    .collect::<RedisResult<Vec<_>>>()
+                    .map_err(config_error)?;
+
+                let mut builder =
+                    SentinelClientBuilder::new(addrs, master_name, SentinelServerType::Master)
+                        .map_err(config_error)?
+                        .set_client_to_redis_db(db);
+                if let Some(username) = &security.username {
+                    builder = builder.set_client_to_redis_username(username.clone());
+                }
+                if let Some(password) = &security.password {
+                    builder = builder.set_client_to_redis_password(password.clone());
+                }
+                if security.tls {
+                    // Sentinels and data nodes are expected to share one TLS setup.
+                    builder = builder
+                        .set_client_to_redis_tls_mode(TlsMode::Secure)
+                        .set_client_to_sentinel_tls_mode(TlsMode::Secure);
+                    if security.has_certificates() {
+                        builder = builder
+                            .set_client_to_redis_certificates(security.certificates())
+                            .set_client_to_sentinel_certificates(security.certificates());
+                    }
+                }
+
+                Self::Sentinel(Mutex::new(builder.build().map_err(config_error)?))
+            }
+            RedisTopology::Cluster { nodes } => {
+                let mut builder = ClusterClient::builder(nodes);
+                if let Some(username) = &security.username {
+                    builder = builder.username(username.clone());
+                }
+                if let Some(password) = &security.password {
+                    builder = builder.password(password.clone());
+                }
+                if security.tls {
+                    builder = builder.tls(TlsMode::Secure);
+                }
+                if security.has_certificates() {
+                    builder = builder.certs(security.certificates());
+                }
+
+                Self::Cluster(builder.build().map_err(config_error)?)
+            }
+        };
+
+        Ok(connector)
+    }
+}
+
+fn uses_tls(topology: &RedisTopology, security: &RedisSecurity) -> bool {
+    match topology {
+        RedisTopology::Standalone { url } => security.tls || url.starts_with("rediss://"),
+        RedisTopology::Cluster { nodes } => {
+            security.tls || nodes.iter().all(|node| node.starts_with("rediss://"))
+        }
+        RedisTopology::Sentinel { .. } => security.tls,
+    }
+}
+
+fn apply_credentials(info: &mut RedisConnectionInfo, security: &RedisSecurity) {
+    // Explicit credentials win over anything embedded in the URL.
+    if let Some(username) = &security.username {
+        info.username = Some(username.clone());
+    }
+    if let Some(password) = &security.password {
+        info.password = Some(password.clone());
+    }
+}
+
+fn config_error(err: redis::RedisError) -> StorageError {
+    StorageError::Config(err.to_string())
+}
+
+/// A pooled connection to whichever `RedisTopology` the store was built with.
+enum RedisConnection {
+    Multiplexed(MultiplexedConnection),
+    Cluster(ClusterConnection),
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Multiplexed(conn) => conn.req_packed_command(cmd),
+            Self::Cluster(conn) => conn.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Multiplexed(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Multiplexed(conn) => conn.get_db(),
+            Self::Cluster(conn) => conn.get_db(),
+        }
+    }
+}
+
+impl ManageConnection for RedisConnector {
+    type Connection = RedisConnection;
+    type Error = redis::RedisError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match self {
+            Self::Standalone(client) => client
+                .get_multiplexed_async_connection()
+                .await
+                .map(RedisConnection::Multiplexed),
+            Self::Sentinel(client) => {
+                // Sentinel is asked for the current master on every new connection,
+                // so connections opened after a failover land on the promoted node.
+                let mut client = client.lock().await;
+                client
+                    .get_async_connection()
+                    .await
+                    .map(RedisConnection::Multiplexed)
+            }
+            Self::Cluster(client) => client
+                .get_async_connection()
+                .await
+                .map(RedisConnection::Cluster),
+        }
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        match self {
+            // After a failover the old master is demoted but still answers PING,
+            // so check the role and let bb8 replace connections to replicas.
+            Self::Sentinel(_) => match redis::cmd("ROLE").query_async(conn).await? {
+                Role::Primary { .. } => Ok(()),
+                _ => Err((
+                    redis::ErrorKind::ReadOnly,
+                    "connected node is no longer the master",
+                )
+                    .into()),
+            },
+            Self::Standalone(_) | Self::Cluster(_) => {
+                let _: String = redis::cmd("PING").query_async(conn).await?;
+                Ok(())
+            }
+        }
+    }
+
+    fn has_broken(&self, _: &mut Self::Connection) -> bool {
+        false
+    }
+}
+
+/// Redis-backed implementation of `SecretStore`.
+///
+/// Secrets are stored as JSON-serialized `Secret` values under keys with a fixed
+/// prefix and a TTL enforced by Redis. Reads run a Lua script that fetches the
+/// record and uses up a view in one server-side step; multi-view secrets keep
+/// their remaining count in a `views:{<prefix><id>}` counter next to the record,
+/// and wrong PINs are counted in `pin_failures:{<prefix><id>}`.
+///
+/// Commands are issued over a pool of multiplexed connections, so concurrent
+/// requests never queue behind one another on a process-wide lock. The pool can
+/// target a standalone server, a Sentinel-managed master or a Redis Cluster (see
+/// [`RedisTopology`]); connections are validated on checkout, so after a
+/// failover stale connections are replaced transparently.
+///
+/// Each secret lives at `<prefix><id>`. Any other key that has to be touched in
+/// the same command as a secret must embed that key as its hash tag, i.e.
+/// `<kind>:{<prefix><id>}`, so both map to the same cluster slot.
+pub struct RedisSecretStore {
+    pool: Pool<RedisConnector>,
+    key_prefix: String,
+}
+
+impl RedisSecretStore {
+    /// Construct a new `RedisSecretStore` from the given Redis URL.
+    pub async fn new(redis_url: &str) -> StorageResult<Self> {
+        Self::with_prefix(redis_url, "secret:").await
+    }
+
+    /// Construct a new `RedisSecretStore` with an explicit key prefix.
+    ///
+    /// This is primarily useful for tests to isolate keys.
+    pub async fn with_prefix(redis_url: &str, key_prefix: &str) -> StorageResult<Self> {
+        Self::with_config(redis_url, key_prefix, RedisPoolConfig::default()).await
+    }
+
+    /// Construct a new `RedisSecretStore` with an explicit key prefix and pool settings.
+    pub async fn with_config(
+        redis_url: &str,
+        key_prefix: &str,
+        pool_config: RedisPoolConfig,
+    ) -> StorageResult<Self> {
+        let topology = RedisTopology::Standalone {
+            url: redis_url.to_string(),
+        };
+        Self::connect(topology, RedisSecurity::default(), key_prefix, pool_config).await
+    }
+
+    /// Construct a new `RedisSecretStore` for any supported topology.
+    ///
+    /// Fails with [`StorageError::Config`] if the settings are unusable and
+    /// with another error if Redis cannot be reached, so callers find out about
+    /// a bad configuration at startup rather than on the first request.
+    pub async fn connect(
+        topology: RedisTopology,
+        security: RedisSecurity,
+        key_prefix: &str,
+        pool_config: RedisPoolConfig,
+    ) -> StorageResult<Self> {
+        let connector = RedisConnector::new(topology, &security)?;
+
+        let pool = Pool::builder()
+            .max_size(pool_config.max_size)
+            .min_idle(pool_config.min_idle)
+            .idle_timeout(pool_config.idle_timeout)
+            .connection_timeout(pool_config.connection_timeout)
+            .build(connector)
+            .await?;
+
+        let store = Self {
+            pool,
+            key_prefix: key_prefix.to_string(),
+        };
+        store.ping().await?;
+
+        Ok(store)
+    }
+
+    fn make_key(&self, id: &str) -> String {
+        format!("{}{}", self.key_prefix, id)
+    }
+
+    /// Key for per-secret state stored alongside the record, hash-tagged so
+    /// it shares the record's cluster slot. Callers reject ids containing
+    /// braces first (see [`is_valid_id`]), as they would break the hash tag.
+    fn companion_key(&self, kind: &str, id: &str) -> String {
+        format!("{}:{{{}}}", kind, self.make_key(id))
+    }
+
+    /// The tombstone a secret leaves if it expires unread. It is written with
+    /// the secret and outlives it, but only counts once the secret is gone;
+    /// reads and burns delete it along with the secret.
+    fn expiry_tombstone_key(&self, id: &str) -> String {
+        self.companion_key("expiry_tombstone", id)
+    }
+
+    /// Marks when a secret's last view was read, standing in for its
+    /// [`Secret::read_tombstone`], which Lua cannot encode.
+    fn read_marker_key(&self, id: &str) -> String {
+        self.companion_key("read", id)
+    }
+
+    /// Receipts are keyed by their own id, not their secret's.
+    fn receipt_key(&self, id: &str) -> String {
+        format!("receipt:{}", self.make_key(id))
+    }
+
+    fn idempotency_key(&self, key: &str) -> String {
+        format!("idempotency:{}", self.make_key(key))
+    }
+
+    /// Store `value` as JSON under `key` with a TTL that runs out at
+    /// `expires_at`, independent of any secret's. Values that have already
+    /// expired are not stored at all.
+    async fn set_json_until(
+        &self,
+        key: String,
+        value: &impl Serialize,
+        expires_at: OffsetDateTime,
+    ) -> StorageResult<()> {
+        let ttl_ms = (expires_at - OffsetDateTime::now_utc()).whole_milliseconds();
+        if ttl_ms <= 0 {
+            return Ok(());
+        }
+
+        let mut conn = self.connection().await?;
+        let json = serde_json::to_string(value)?;
+
+        let _: () = redis::cmd("SET")
+            .arg(key)
+            .arg(json)
+            .arg("PX")
+            .arg(ttl_ms as u64)
+            .query_async(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, key: String) -> StorageResult<Option<T>> {
+        let mut conn = self.connection().await?;
+
+        let json: Option<String> = redis::cmd("GET").arg(key).query_async(&mut *conn).await?;
+
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    async fn connection(&self) -> StorageResult<PooledConnection<'_, RedisConnector>> {
+        self.pool.get().await.map_err(|err| match err {
+            RunError::User(err) => err.into(),
+            RunError::TimedOut => {
+                StorageError::Timeout("timed out waiting for a Redis connection".to_string())
+            }
+        })
+    }
+
+    /// MGET of a secret's record and its companion counters. All three keys
+    /// share a hash slot, so this works on a cluster too.
+    fn peek_command(&self, id: &str) -> redis::Cmd {
+        let mut cmd = redis::cmd("MGET");
+        cmd.arg(self.make_key(id))
+            .arg(self.companion_key("views", id))
+            .arg(self.companion_key("pin_failures", id));
+        cmd
+    }
+}
+
+/// A secret's record, view counter and failed PIN counter, as read by MGET.
+type PeekedRecord = (Option<String>, Option<u32>, Option<u32>);
+
+/// What [`RedisSecretStore::get_tombstone`] reads: the tombstone, the read
+/// marker and its PTTL, whether the secret exists, and its expiry tombstone.
+type TombstoneKeys = (Option<String>, Option<i64>, i64, bool, Option<String>);
+
+fn unix_millis(at: OffsetDateTime) -> i64 {
+    (at.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+fn from_unix_millis(millis: i64) -> StorageResult<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+        .map_err(|err| StorageError::Serialization(err.to_string()))
+}
+
+/// The JSON expiry tombstone of `secret` and its TTL in milliseconds, or an
+/// empty string when it leaves none.
+fn expiry_tombstone_args(secret: &Secret) -> StorageResult<(String, i64)> {
+    let Some(tombstone) = secret.expiry_tombstone() else {
+        return Ok((String::new(), 0));
+    };
+
+    let ttl_ms = (tombstone.expires_at - OffsetDateTime::now_utc()).whole_milliseconds();
+    if ttl_ms <= 0 {
+        return Ok((String::new(), 0));
+    }
+    Ok((serde_json::to_string(&tombstone)?, ttl_ms as i64))
+}
+
+fn decode_peeked((json, views, failures): PeekedRecord) -> StorageResult<Option<Secret>> {
+    let Some(json) = json else {
+        return Ok(None);
+    };
+
+    let mut secret: Secret = serde_json::from_str(&json)?;
+    secret.remaining_views = views.unwrap_or(1);
+    secret.failed_pin_attempts = failures.unwrap_or(0);
+    Ok(Some(secret))
+}
+
+/// Store the secret and, for multi-view secrets, its view counter, along with
+/// the tombstone it leaves if it expires unread.
+///
+/// KEYS[1] = secret key, KEYS[2] = view counter key, KEYS[3] = expiry
+/// tombstone key; ARGV[1] = JSON record, ARGV[2] = TTL in seconds,
+/// ARGV[3] = views, ARGV[4] = JSON expiry tombstone (empty for none),
+/// ARGV[5] = its TTL in milliseconds.
+static INSERT_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+        if tonumber(ARGV[3]) > 1 then
+            redis.call('SET', KEYS[2], ARGV[3], 'EX', ARGV[2])
+        end
+        if ARGV[4] ~= '' then
+            redis.call('SET', KEYS[3], ARGV[4], 'PX', ARGV[5])
+        end
+        ",
+    )
+});
+
+/// Return the secret and use up one view, deleting it and its companion keys
+/// on the last one and leaving a read marker in their place.
+///
+/// KEYS[1] = secret key, KEYS[2] = view counter key, KEYS[3] = PIN failure key,
+/// KEYS[4] = expiry tombstone key, KEYS[5] = read marker key; ARGV[1] = the
+/// time of the read in unix milliseconds. Single-view secrets have no counter
+/// key; `DECR` then yields -1, which is treated the same as reaching zero. The
+/// read marker holds ARGV[1] and lasts for the record's `tombstone_secs`.
+/// Returns `{record, remaining_views}`, or nil when the secret does not exist.
+static CONSUME_VIEW_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local record = redis.call('GET', KEYS[1])
+        if not record then
+            return false
+        end
+        local remaining = redis.call('DECR', KEYS[2])
+        if remaining <= 0 then
+            redis.call('DEL', KEYS[1], KEYS[2], KEYS[3], KEYS[4])
+            local tombstone_secs = cjson.decode(record)['tombstone_secs']
+            if type(tombstone_secs) == 'number' and tombstone_secs > 0 then
+                redis.call('SET', KEYS[5], ARGV[1], 'PX', tombstone_secs * 1000)
+            end
+            remaining = 0
+        end
+        return {record, remaining}
+        ",
+    )
+});
+
+/// Count a wrong PIN, burning the secret once ARGV[1] wrong PINs were entered.
+///
+/// Same first four keys as [`CONSUME_VIEW_SCRIPT`]. The failure counter expires with the
+/// secret. Returns the attempts left, or nil when the secret does not exist.
+static FAILED_PIN_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local ttl = redis.call('PTTL', KEYS[1])
+        if ttl < 0 then
+            return false
+        end
+        local failures = redis.call('INCR', KEYS[3])
+        if failures == 1 then
+            redis.call('PEXPIRE', KEYS[3], ttl)
+        end
+        local left = tonumber(ARGV[1]) - failures
+        if left <= 0 then
+            redis.call('DEL', KEYS[1], KEYS[2], KEYS[3], KEYS[4])
+            left = 0
+        end
+        return left
+        ",
+    )
+});
+
+/// Replace the record of a live secret and move its expiry, along with its
+/// companion keys' and its expiry tombstone's.
+///
+/// KEYS[1] = secret key, KEYS[2] = expiry tombstone key, KEYS[3..] = companion
+/// keys; ARGV[1] = JSON record, ARGV[2] = TTL in milliseconds, ARGV[3] = JSON
+/// expiry tombstone (empty for none), ARGV[4] = its TTL in milliseconds.
+/// Returns 1, or 0 when the secret does not exist.
+static SET_TTL_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        if redis.call('EXISTS', KEYS[1]) == 0 then
+            return 0
+        end
+        redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+        if ARGV[3] ~= '' then
+            redis.call('SET', KEYS[2], ARGV[3], 'PX', ARGV[4])
+        else
+            redis.call('DEL', KEYS[2])
+        end
+        for i = 3, #KEYS do
+            redis.call('PEXPIRE', KEYS[i], ARGV[2])
+        end
+        return 1
+        ",
+    )
+});
+
+/// Delete a live secret along with its companion keys and expiry tombstone.
+///
+/// Same first four keys as [`CONSUME_VIEW_SCRIPT`]. Companion keys never outlive the
+/// record, so any of them being deleted means the secret was live; the expiry
+/// tombstone does outlive it, so it is kept for a secret that already expired.
+/// Returns 1, or 0 when the secret does not exist.
+static BURN_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        if redis.call('DEL', KEYS[1], KEYS[2], KEYS[3]) == 0 then
+            return 0
+        end
+        redis.call('DEL', KEYS[4])
+        return 1
+        ",
+    )
+});
+
+/// Store a value unless the key already holds one, which is returned instead.
+///
+/// KEYS[1] = key; ARGV[1] = value, ARGV[2] = TTL in milliseconds.
+static SET_IF_ABSENT_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local existing = redis.call('GET', KEYS[1])
+        if existing then
+            return existing
+        end
+        redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+        return false
+        ",
+    )
+});
+
+#[async_trait]
+impl SecretStore for RedisSecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        let json = serde_json::to_string(&secret)?;
+        // An embargoed secret's TTL may only start counting at `not_before`.
+        let ttl_secs = (secret.expires_at() - OffsetDateTime::now_utc())
+            .whole_seconds()
+            .max(1);
+
+        let (tombstone, tombstone_ttl_ms) = expiry_tombstone_args(&secret)?;
+
+        let mut conn = self.connection().await?;
+        let _: () = INSERT_SCRIPT
+            .key(self.make_key(&secret.id))
+            .key(self.companion_key("views", &secret.id))
+            .key(self.expiry_tombstone_key(&secret.id))
+            .arg(json)
+            .arg(ttl_secs)
+            .arg(secret.remaining_views)
+            .arg(tombstone)
+            .arg(tombstone_ttl_ms)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let mut conn = self.connection().await?;
+
+        // The script reads, decrements and (on the last view) deletes in a
+        // single server-side step, so backends sharing the same Redis can never
+        // hand out more views than were granted.
+        let read: Option<(String, u32)> = CONSUME_VIEW_SCRIPT
+            .key(self.make_key(id))
+            .key(self.companion_key("views", id))
+            .key(self.companion_key("pin_failures", id))
+            .key(self.expiry_tombstone_key(id))
+            .key(self.read_marker_key(id))
+            .arg(unix_millis(now))
+            .invoke_async(&mut *conn)
+            .await?;
+
+        if let Some((json, remaining_views)) = read {
+            let mut secret: Secret = serde_json::from_str(&json)?;
+            secret.mark_read(now);
+            secret.remaining_views = remaining_views;
+
+            Ok(Some(secret))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let mut conn = self.connection().await?;
+
+        let record: PeekedRecord = self.peek_command(id).query_async(&mut *conn).await?;
+
+        decode_peeked(record)
+    }
+
+    async fn secret_metadata(&self, id: &str) -> StorageResult<Option<SecretMetadata>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let mut conn = self.connection().await?;
+
+        let (record, pttl): (PeekedRecord, i64) = redis::pipe()
+            .add_command(self.peek_command(id))
+            .cmd("PTTL")
+            .arg(self.make_key(id))
+            .query_async(&mut *conn)
+            .await?;
+
+        // Redis' own TTL is what actually decides when the key disappears.
+        Ok(decode_peeked(record)?.map(|secret| {
+            let mut metadata = secret.metadata();
+            if pttl >= 0 {
+                metadata.expires_at =
+                    OffsetDateTime::now_utc() + time::Duration::milliseconds(pttl);
+            }
+            metadata
+        }))
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let mut conn = self.connection().await?;
+
+        let attempts_left: Option<u32> = FAILED_PIN_SCRIPT
+            .key(self.make_key(id))
+            .key(self.companion_key("views", id))
+            .key(self.companion_key("pin_failures", id))
+            .key(self.expiry_tombstone_key(id))
+            .arg(max_attempts)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        Ok(attempts_left)
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        if !is_valid_id(id) {
+            return Ok(false);
+        }
+
+        let mut conn = self.connection().await?;
+
+        // Companion keys share the record's hash slot, so this works on a cluster.
+        let burned: u32 = BURN_SCRIPT
+            .key(self.make_key(id))
+            .key(self.companion_key("views", id))
+            .key(self.companion_key("pin_failures", id))
+            .key(self.expiry_tombstone_key(id))
+            .invoke_async(&mut *conn)
+            .await?;
+
+        Ok(burned > 0)
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let Some(mut secret) = self.peek_secret(id).await? else {
+            return Ok(None);
+        };
+        secret.ttl_secs = ttl_secs;
+
+        let ttl_ms = (secret.expires_at() - OffsetDateTime::now_utc()).whole_milliseconds();
+        if ttl_ms <= 0 {
+            return Ok(None);
+        }
+
+        // The record itself never changes after creation (views and PIN
+        // failures live in the companion keys), so rewriting it is safe as
+        // long as the key still exists.
+        let (tombstone, tombstone_ttl_ms) = expiry_tombstone_args(&secret)?;
+
+        let mut conn = self.connection().await?;
+        let updated: u32 = SET_TTL_SCRIPT
+            .key(self.make_key(id))
+            .key(self.expiry_tombstone_key(id))
+            .key(self.companion_key("views", id))
+            .key(self.companion_key("pin_failures", id))
+            .arg(serde_json::to_string(&secret)?)
+            .arg(ttl_ms as u64)
+            .arg(tombstone)
+            .arg(tombstone_ttl_ms)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        Ok((updated > 0).then_some(secret))
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        self.set_json_until(
+            self.companion_key("tombstone", id),
+            tombstone,
+            tombstone.expires_at,
+        )
+        .await
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let mut conn = self.connection().await?;
+
+        let (tombstone, read_at, read_pttl, live, expiry_tombstone): TombstoneKeys = redis::pipe()
+            .atomic()
+            .cmd("GET")
+            .arg(self.companion_key("tombstone", id))
+            .cmd("GET")
+            .arg(self.read_marker_key(id))
+            .cmd("PTTL")
+            .arg(self.read_marker_key(id))
+            .cmd("EXISTS")
+            .arg(self.make_key(id))
+            .cmd("GET")
+            .arg(self.expiry_tombstone_key(id))
+            .query_async(&mut *conn)
+            .await?;
+
+        if let Some(json) = tombstone {
+            return Ok(Some(serde_json::from_str(&json)?));
+        }
+        if let Some(read_at) = read_at {
+            let now = OffsetDateTime::now_utc();
+            return Ok(Some(Tombstone {
+                read_at: Some(from_unix_millis(read_at)?),
+                ..Tombstone::new(
+                    TombstoneReason::Read,
+                    now + time::Duration::milliseconds(read_pttl.max(0)),
+                )
+            }));
+        }
+
+        // Redis drops an expired secret on its own, so the expiry tombstone
+        // only counts once the record is gone.
+        let json = expiry_tombstone.filter(|_| !live);
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        if !is_valid_id(&receipt.id) {
+            return Ok(());
+        }
+
+        self.set_json_until(self.receipt_key(&receipt.id), receipt, receipt.expires_at)
+            .await
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        self.get_json(self.receipt_key(id)).await
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        let mut conn = self.connection().await?;
+        let _: () = redis::cmd("DEL")
+            .arg(self.receipt_key(id))
+            .query_async(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        // Keys are hashes of the client's header, never the header itself.
+        debug_assert!(is_valid_id(key), "idempotency key is not a valid Redis key");
+
+        // Redis expires the record itself, so any record found is live.
+        let ttl_ms = (record.expires_at - OffsetDateTime::now_utc())
+            .whole_milliseconds()
+            .max(1);
+
+        let mut conn = self.connection().await?;
+        let existing: Option<String> = SET_IF_ABSENT_SCRIPT
+            .key(self.idempotency_key(key))
+            .arg(serde_json::to_string(record)?)
+            .arg(ttl_ms as u64)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        Ok(existing
+            .map(|json| serde_json::from_str(&json))
+            .transpose()?)
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        if !is_valid_id(key) {
+            return Ok(None);
+        }
+
+        self.get_json(self.idempotency_key(key)).await
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        if !is_valid_id(key) {
+            return Ok(());
+        }
+
+        self.set_json_until(self.idempotency_key(key), record, record.expires_at)
+            .await
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        let mut conn = self.connection().await?;
+
+        let _: String = redis::cmd("PING").query_async(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Only with the append-only file enabled; RDB snapshots alone can lose
+    /// minutes of writes. A cluster must have it on every node.
+    async fn is_durable(&self) -> StorageResult<bool> {
+        let mut conn = self.connection().await?;
+
+        let info: Value = redis::cmd("INFO")
+            .arg("persistence")
+            .query_async(&mut *conn)
+            .await?;
+
+        Ok(aof_enabled(&info))
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        let state = self.pool.state();
+
+        Some(PoolStats {
+            max_size: self.pool.config().max_size,
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            gets_waited: state.statistics.get_waited,
+            gets_timed_out: state.statistics.get_timed_out,
+            connections_created: state.statistics.connections_created,
+            connections_closed_idle_timeout: state.statistics.connections_closed_idle_timeout,
+        })
+    }
+}
+
+/// Whether an `INFO persistence` reply reports `aof_enabled:1`. Clusters
+/// answer with one reply per node, all of which must agree.
+fn aof_enabled(info: &Value) -> bool {
+    let text_enabled = |text: &str| text.lines().any(|line| line.trim() == "aof_enabled:1");
+
+    match info {
+        Value::BulkString(bytes) => text_enabled(&String::from_utf8_lossy(bytes)),
+        Value::SimpleString(text) | Value::VerbatimString { text, .. } => text_enabled(text),
+        Value::Map(replies) => {
+            !replies.is_empty() && replies.iter().all(|(_, reply)| aof_enabled(reply))
+        }
+        Value::Array(replies) => !replies.is_empty() && replies.iter().all(aof_enabled),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standalone(url: &str) -> RedisTopology {
+        RedisTopology::Standalone {
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn certificates_require_a_tls_connection() {
+        let security = RedisSecurity {
+            ca_cert: Some(b"-----BEGIN CERTIFICATE-----".to_vec()),
+            ..RedisSecurity::default()
+        };
+
+        let err = RedisConnector::new(standalone("redis://127.0.0.1:6379"), &security)
+            .err()
+            .expect("plaintext URL with a CA bundle must be rejected");
+        assert!(matches!(err, StorageError::Config(_)));
+    }
+
+    #[test]
+    fn unparsable_ca_bundle_is_a_config_error() {
+        let security = RedisSecurity {
+            ca_cert: Some(b"not a certificate".to_vec()),
+            ..RedisSecurity::default()
+        };
+
+        let err = RedisConnector::new(standalone("rediss://127.0.0.1:6379"), &security)
+            .err()
+            .expect("garbage CA bundle must be rejected");
+        assert!(matches!(err, StorageError::Config(_)));
+    }
+
+    #[test]
+    fn username_without_password_is_rejected() {
+        let security = RedisSecurity {
+            username: Some("cendre".to_string()),
+            ..RedisSecurity::default()
+        };
+
+        let err = RedisConnector::new(standalone("redis://127.0.0.1:6379"), &security)
+            .err()
+            .expect("username alone must be rejected");
+        assert!(matches!(err, StorageError::Config(_)));
+    }
+
+    #[test]
+    fn debug_output_redacts_credentials() {
+        let security = RedisSecurity {
+            username: Some("cendre".to_string()),
+            password: Some("hunter2".to_string()),
+            tls: true,
+            ca_cert: None,
+            client_cert: Some(RedisClientCert {
+                cert: b"CERT".to_vec(),
+                key: b"PRIVATE KEY".to_vec(),
+            }),
+        };
+
+        let rendered = format!("{security:?}");
+        assert!(rendered.contains("cendre"));
+        assert!(!rendered.contains("hunter2"));
+        assert!(!rendered.contains("PRIVATE KEY"));
+    }
+    #[test]
+    fn aof_must_be_enabled_everywhere() {
+        let node = |aof: u8| {
+            Value::BulkString(
+                format!("# Persistence\r\nloading:0\r\naof_enabled:{aof}\r\n").into_bytes(),
+            )
+        };
+
+        assert!(aof_enabled(&node(1)));
+        assert!(!aof_enabled(&node(0)));
+        assert!(aof_enabled(&Value::Map(vec![
+            (Value::SimpleString("a:6379".into()), node(1)),
+            (Value::SimpleString("b:6379".into()), node(1)),
+        ])));
+        assert!(!aof_enabled(&Value::Map(vec![
+            (Value::SimpleString("a:6379".into()), node(1)),
+            (Value::SimpleString("b:6379".into()), node(0)),
+        ])));
+        assert!(!aof_enabled(&Value::Nil));
+    }
+}
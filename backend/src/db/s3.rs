@@ -0,0 +1,725 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::error::{DisplayErrorContext, SdkError};
+use aws_sdk_s3::primitives::{ByteStream, DateTime};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+
+use super::{SecretStore, StorageError, StorageResult, is_valid_id};
+use crate::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+
+/// How many times a read retries after losing a conditional write.
+const MAX_READ_ATTEMPTS: usize = 8;
+
+/// Object metadata key holding the secret's expiry as unix seconds.
+const EXPIRES_AT_METADATA: &str = "expires-at";
+
+/// Folder, under the key prefix, of the empty markers indexing objects by expiry.
+const EXPIRY_INDEX: &str = "expiry/";
+
+/// Precondition for [`S3SecretStore::put_json`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WriteIf<'a> {
+    Always,
+    /// The object still has this ETag.
+    Matches(&'a str),
+    /// There is no object yet.
+    Absent,
+}
+
+/// Connection settings for `S3SecretStore`.
+#[derive(Clone, Debug)]
+pub struct S3StoreConfig {
+    /// Custom endpoint for S3-compatible services such as MinIO; `None` uses AWS.
+    pub endpoint: Option<String>,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Prefix prepended to every object key, e.g. `secrets/`.
+    pub key_prefix: String,
+    /// Address buckets as `endpoint/bucket` rather than `bucket.endpoint`, as MinIO expects.
+    pub force_path_style: bool,
+}
+
+/// S3-compatible object storage implementation of `SecretStore`.
+///
+/// Each secret is an object keyed by its id, holding the JSON-serialized
+/// `Secret` with its expiry recorded in object metadata. A read fetches the
+/// object and then, with `If-Match` on the ETag it saw, either deletes it (last
+/// view) or rewrites it with one view fewer. When several readers race only the
+/// one whose conditional request lands gets the view; the others start over.
+/// The endpoint must support conditional deletes and writes (AWS S3 and recent
+/// MinIO releases do). Expired objects are removed by the sweeper started with
+/// [`S3SecretStore::spawn_sweeper`] rather than left to bucket lifecycle rules.
+/// Every write also leaves an empty marker under `expiry/` named after the
+/// object's expiry time, so the sweeper lists only what has fallen due instead
+/// of inspecting every object in the bucket.
+pub struct S3SecretStore {
+    /// Shared with the sweeper only through a weak reference, so dropping the
+    /// store stops the sweeper.
+    client: Arc<Client>,
+    bucket: String,
+    key_prefix: String,
+}
+
+impl S3SecretStore {
+    /// Build a client for the configured endpoint and check the bucket is reachable.
+    pub async fn new(config: S3StoreConfig) -> StorageResult<Self> {
+        let credentials = Credentials::new(
+            config.access_key_id,
+            config.secret_access_key,
+            None,
+            None,
+            "cendre",
+        );
+
+        let mut builder = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(config.region))
+            .credentials_provider(credentials)
+            .force_path_style(config.force_path_style);
+        if let Some(endpoint) = config.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+
+        let store = Self {
+            client: Arc::new(Client::from_conf(builder.build())),
+            bucket: config.bucket,
+            key_prefix: config.key_prefix,
+        };
+        store.ping().await?;
+
+        Ok(store)
+    }
+
+    /// Start a background task that deletes expired objects every `interval`.
+    ///
+    /// The task only holds a weak reference to the store's client and exits
+    /// once the store has been dropped.
+    pub fn spawn_sweeper(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let client = Arc::downgrade(&self.client);
+        let bucket = self.bucket.clone();
+        let key_prefix = self.key_prefix.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(client) = client.upgrade() else {
+                    break;
+                };
+                let store = Self {
+                    client,
+                    bucket: bucket.clone(),
+                    key_prefix: key_prefix.clone(),
+                };
+
+                match store.purge_expired(OffsetDateTime::now_utc()).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::debug!(purged, "swept expired secrets from S3 store"),
+                    Err(err) => tracing::warn!("failed to sweep S3 store: {:?}", err),
+                }
+            }
+        })
+    }
+
+    fn make_key(&self, id: &str) -> String {
+        format!("{}{}", self.key_prefix, id)
+    }
+
+    /// Tombstones live beside the secrets, so the sweeper expires them too.
+    fn tombstone_key(&self, id: &str) -> String {
+        format!("{}tombstones/{}", self.key_prefix, id)
+    }
+
+    /// Receipts are keyed by their own id and swept like tombstones.
+    fn receipt_key(&self, id: &str) -> String {
+        format!("{}receipts/{}", self.key_prefix, id)
+    }
+
+    fn idempotency_key(&self, key: &str) -> String {
+        format!("{}idempotency/{}", self.key_prefix, key)
+    }
+
+    async fn purge_expired(&self, now: OffsetDateTime) -> StorageResult<usize> {
+        let mut purged = 0;
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(format!("{}{EXPIRY_INDEX}", self.key_prefix))
+            .into_paginator()
+            .send();
+
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(sdk_error)?;
+
+            for marker in page.contents().iter().filter_map(|object| object.key()) {
+                if let Some((expires_at, key)) = parse_expiry_marker(&self.key_prefix, marker) {
+                    // Markers are listed in expiry order, so the rest are not due either.
+                    if expires_at > now.unix_timestamp() {
+                        return Ok(purged);
+                    }
+                    let removed = match self.secret_id_of(&key) {
+                        Some(id) => self.purge_secret(id, &key, now).await?,
+                        None => self.purge_object(&key, now).await?,
+                    };
+                    if removed {
+                        purged += 1;
+                    }
+                }
+                self.delete_if_match(marker, None).await?;
+            }
+        }
+
+        Ok(purged)
+    }
+
+    /// The id of the secret stored at `key`, or `None` for the other objects,
+    /// which all live under a folder of their own.
+    fn secret_id_of<'k>(&self, key: &'k str) -> Option<&'k str> {
+        key.strip_prefix(&self.key_prefix)
+            .filter(|name| !name.contains('/'))
+    }
+
+    /// Delete the secret at `key` if it has expired at `now`, leaving its
+    /// tombstone behind.
+    async fn purge_secret(&self, id: &str, key: &str, now: OffsetDateTime) -> StorageResult<bool> {
+        let (secret, e_tag) = match self.fetch_json::<Secret>(key).await {
+            Ok(Some(found)) => found,
+            Ok(None) => return Ok(false),
+            // A record that cannot be decoded has no tombstone to leave.
+            Err(StorageError::Serialization(_)) => return self.purge_object(key, now).await,
+            Err(err) => return Err(err),
+        };
+
+        if !secret.is_expired_at(now) || !self.delete_if_match(key, e_tag.as_deref()).await? {
+            return Ok(false);
+        }
+        self.put_expiry_tombstone(id, &secret, now).await?;
+
+        Ok(true)
+    }
+
+    /// Leave the tombstone of a secret that ran out unread, if it asked for one.
+    async fn put_expiry_tombstone(
+        &self,
+        id: &str,
+        secret: &Secret,
+        now: OffsetDateTime,
+    ) -> StorageResult<()> {
+        match secret.expiry_tombstone() {
+            Some(tombstone) if !tombstone.is_expired_at(now) => {
+                self.put_tombstone(id, &tombstone).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete the object at `key` if it has expired at `now`.
+    ///
+    /// An object re-timed since its marker was written is left alone; the
+    /// write that moved its expiry left a marker for the new time.
+    async fn purge_object(&self, key: &str, now: OffsetDateTime) -> StorageResult<bool> {
+        let head = match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(head) => head,
+            // Already gone, most likely read before it expired.
+            Err(err) if status_of(&err) == Some(404) => return Ok(false),
+            Err(err) => return Err(sdk_error(err)),
+        };
+
+        let expires_at = head
+            .metadata()
+            .and_then(|metadata| metadata.get(EXPIRES_AT_METADATA))
+            .and_then(|value| value.parse::<i64>().ok());
+
+        // Objects without readable expiry metadata can never be served safely.
+        let expired = expires_at.is_none_or(|at| at <= now.unix_timestamp());
+        if !expired {
+            return Ok(false);
+        }
+        self.delete_if_match(key, head.e_tag()).await
+    }
+
+    /// Write `secret`, or with `if_match` overwrite it only if it still has
+    /// that ETag.
+    ///
+    /// Returns `false` when a conditional overwrite lost to another writer.
+    async fn put_secret(&self, secret: &Secret, if_match: Option<&str>) -> StorageResult<bool> {
+        let condition = match if_match {
+            Some(e_tag) => WriteIf::Matches(e_tag),
+            None => WriteIf::Always,
+        };
+        self.put_json(
+            self.make_key(&secret.id),
+            secret,
+            secret.expires_at(),
+            condition,
+        )
+        .await
+    }
+
+    /// Write `value` as JSON to `key`, recording `expires_at` for the sweeper.
+    ///
+    /// Returns `false` when `condition` did not hold.
+    async fn put_json(
+        &self,
+        key: String,
+        value: &impl Serialize,
+        expires_at: OffsetDateTime,
+        condition: WriteIf<'_>,
+    ) -> StorageResult<bool> {
+        let body = serde_json::to_vec(value)?;
+        let expires_at = expires_at.unix_timestamp();
+
+        // Index the object first, so a crash in between leaves behind only a
+        // marker, which the sweeper discards, rather than an object it never visits.
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(expiry_marker_key(&self.key_prefix, &key, expires_at))
+            .body(ByteStream::from_static(&[]))
+            .send()
+            .await
+            .map_err(sdk_error)?;
+
+        let mut request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type("application/json")
+            .metadata(EXPIRES_AT_METADATA, expires_at.to_string())
+            .expires(DateTime::from_secs(expires_at))
+            .body(ByteStream::from(body));
+        match condition {
+            WriteIf::Always => {}
+            WriteIf::Matches(e_tag) => request = request.if_match(e_tag),
+            WriteIf::Absent => request = request.if_none_match("*"),
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            // 409 is returned when a concurrent conditional write is in progress.
+            Err(err)
+                if condition != WriteIf::Always
+                    && matches!(status_of(&err), Some(404 | 409 | 412)) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(sdk_error(err)),
+        }
+    }
+
+    /// Delete `key` only if it still has the given ETag.
+    ///
+    /// Returns `false` when someone else deleted or replaced the object first.
+    async fn delete_if_match(&self, key: &str, e_tag: Option<&str>) -> StorageResult<bool> {
+        let mut request = self.client.delete_object().bucket(&self.bucket).key(key);
+        if let Some(e_tag) = e_tag {
+            request = request.if_match(e_tag);
+        }
+
+        match request.send().await {
+            Ok(_) => Ok(true),
+            Err(err) if matches!(status_of(&err), Some(404 | 412)) => Ok(false),
+            Err(err) => Err(sdk_error(err)),
+        }
+    }
+
+    /// Read and decode the object at `key` along with its ETag.
+    async fn fetch_json<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> StorageResult<Option<(T, Option<String>)>> {
+        let object = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(object) => object,
+            Err(err) if status_of(&err) == Some(404) => return Ok(None),
+            Err(err) => return Err(sdk_error(err)),
+        };
+
+        let e_tag = object.e_tag().map(str::to_string);
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::Unavailable(e.to_string()))?
+            .into_bytes();
+
+        Ok(Some((serde_json::from_slice(&body)?, e_tag)))
+    }
+
+    /// Apply `update` to the secret `id` with a conditional write, deleting it
+    /// instead when `update` returns `true`.
+    ///
+    /// Returns the updated record, or `None` if the secret does not exist or
+    /// has expired (in which case it is deleted).
+    async fn modify_secret(
+        &self,
+        id: &str,
+        mut update: impl FnMut(&mut Secret) -> bool,
+    ) -> StorageResult<Option<Secret>> {
+        let key = self.make_key(id);
+
+        // Losing a conditional write to another writer just means starting
+        // over from the record it left behind.
+        for _ in 0..MAX_READ_ATTEMPTS {
+            let Some((mut secret, e_tag)) = self.fetch_json::<Secret>(&key).await? else {
+                return Ok(None);
+            };
+
+            // Expired secrets are burned without being returned.
+            let now = OffsetDateTime::now_utc();
+            if secret.is_expired_at(now) {
+                if self.delete_if_match(&key, e_tag.as_deref()).await? {
+                    self.put_expiry_tombstone(id, &secret, now).await?;
+                }
+                return Ok(None);
+            }
+
+            // Only the writer whose conditional request succeeds may apply its update.
+            let won = if update(&mut secret) {
+                let deleted = self.delete_if_match(&key, e_tag.as_deref()).await?;
+                if deleted {
+                    if let Some(tombstone) = secret.read_tombstone() {
+                        self.put_tombstone(id, &tombstone).await?;
+                    }
+                }
+                deleted
+            } else {
+                self.put_secret(&secret, e_tag.as_deref()).await?
+            };
+            if won {
+                return Ok(Some(secret));
+            }
+        }
+
+        Err(StorageError::Conflict(format!(
+            "gave up updating secret after {MAX_READ_ATTEMPTS} conflicting writes"
+        )))
+    }
+}
+
+/// Key of the empty marker recording that the object at `key` expires at
+/// `expires_at` (unix seconds). Zero-padding makes markers sort by expiry.
+fn expiry_marker_key(key_prefix: &str, key: &str, expires_at: i64) -> String {
+    let name = key.strip_prefix(key_prefix).unwrap_or(key);
+    format!("{key_prefix}{EXPIRY_INDEX}{:020}/{name}", expires_at.max(0))
+}
+
+/// Split a marker key into the expiry it records and the key of its object.
+fn parse_expiry_marker(key_prefix: &str, marker: &str) -> Option<(i64, String)> {
+    let rest = marker
+        .strip_prefix(key_prefix)?
+        .strip_prefix(EXPIRY_INDEX)?;
+    let (expires_at, name) = rest.split_once('/')?;
+
+    Some((expires_at.parse().ok()?, format!("{key_prefix}{name}")))
+}
+
+fn status_of<E>(err: &SdkError<E, HttpResponse>) -> Option<u16> {
+    err.raw_response()
+        .map(|response| response.status().as_u16())
+}
+
+/// Classify an S3 SDK error into a [`StorageError`].
+fn sdk_error<E>(err: SdkError<E, HttpResponse>) -> StorageError
+where
+    E: std::error::Error + 'static,
+{
+    let message = DisplayErrorContext(&err).to_string();
+    match &err {
+        SdkError::TimeoutError(_) => StorageError::Timeout(message),
+        SdkError::DispatchFailure(failure) if failure.is_timeout() => {
+            StorageError::Timeout(message)
+        }
+        SdkError::DispatchFailure(failure) if failure.is_io() => StorageError::Unavailable(message),
+        SdkError::ResponseError(_) => StorageError::Serialization(message),
+        _ => match status_of(&err) {
+            Some(409 | 412) => StorageError::Conflict(message),
+            // 503 covers S3's SlowDown throttling as well as real outages.
+            Some(500 | 502 | 503) => StorageError::Unavailable(message),
+            Some(504) => StorageError::Timeout(message),
+            _ => StorageError::Backend(message),
+        },
+    }
+}
+
+#[async_trait]
+impl SecretStore for S3SecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        self.put_secret(&secret, None).await?;
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        self.modify_secret(id, |secret| secret.consume_view(now))
+            .await
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let secret = self.fetch_json::<Secret>(&self.make_key(id)).await?;
+
+        Ok(secret
+            .map(|(secret, _)| secret)
+            .filter(|secret| !secret.is_expired_at(now)))
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let secret = self
+            .modify_secret(id, |secret| secret.record_failed_pin(max_attempts) == 0)
+            .await?;
+
+        Ok(secret.map(|secret| max_attempts.saturating_sub(secret.failed_pin_attempts)))
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        if !is_valid_id(id) {
+            return Ok(false);
+        }
+
+        Ok(self.modify_secret(id, |_| true).await?.is_some())
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        self.modify_secret(id, |secret| {
+            secret.ttl_secs = ttl_secs;
+            false
+        })
+        .await
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        self.put_json(
+            self.tombstone_key(id),
+            tombstone,
+            tombstone.expires_at,
+            WriteIf::Always,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let tombstone = match self
+            .fetch_json::<Tombstone>(&self.tombstone_key(id))
+            .await?
+        {
+            Some((tombstone, _)) => Some(tombstone),
+            // Expired secrets stay in the bucket until the sweeper gets to them.
+            None => self
+                .fetch_json::<Secret>(&self.make_key(id))
+                .await?
+                .map(|(secret, _)| secret)
+                .filter(|secret| secret.is_expired_at(now))
+                .and_then(|secret| secret.expiry_tombstone()),
+        };
+
+        Ok(tombstone.filter(|tombstone| !tombstone.is_expired_at(now)))
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        if !is_valid_id(&receipt.id) {
+            return Ok(());
+        }
+
+        self.put_json(
+            self.receipt_key(&receipt.id),
+            receipt,
+            receipt.expires_at,
+            WriteIf::Always,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let receipt = self.fetch_json::<Receipt>(&self.receipt_key(id)).await?;
+
+        Ok(receipt
+            .map(|(receipt, _)| receipt)
+            .filter(|receipt| !receipt.is_expired_at(OffsetDateTime::now_utc())))
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        if !is_valid_id(id) {
+            return Ok(());
+        }
+
+        // Its expiry marker is discarded by the sweeper once due.
+        self.delete_if_match(&self.receipt_key(id), None).await?;
+        Ok(())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        // Keys are hashes of the client's header, never the header itself.
+        debug_assert!(
+            is_valid_id(key),
+            "idempotency key is not a valid object name"
+        );
+
+        let object_key = self.idempotency_key(key);
+
+        for _ in 0..MAX_READ_ATTEMPTS {
+            if self
+                .put_json(
+                    object_key.clone(),
+                    record,
+                    record.expires_at,
+                    WriteIf::Absent,
+                )
+                .await?
+            {
+                return Ok(None);
+            }
+
+            // The sweeper may not have got to an expired record yet.
+            match self.fetch_json::<IdempotencyRecord>(&object_key).await? {
+                Some((existing, _)) if !existing.is_expired_at(OffsetDateTime::now_utc()) => {
+                    return Ok(Some(existing));
+                }
+                Some((_, e_tag)) => {
+                    self.delete_if_match(&object_key, e_tag.as_deref()).await?;
+                }
+                None => {}
+            }
+        }
+
+        Err(StorageError::Conflict(format!(
+            "gave up saving idempotency record after {MAX_READ_ATTEMPTS} conflicting writes"
+        )))
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        if !is_valid_id(key) {
+            return Ok(None);
+        }
+
+        let record = self
+            .fetch_json::<IdempotencyRecord>(&self.idempotency_key(key))
+            .await?;
+
+        Ok(record
+            .map(|(record, _)| record)
+            .filter(|record| !record.is_expired_at(OffsetDateTime::now_utc())))
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        if !is_valid_id(key) {
+            return Ok(());
+        }
+
+        self.put_json(
+            self.idempotency_key(key),
+            record,
+            record.expires_at,
+            WriteIf::Always,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(sdk_error)?;
+
+        Ok(())
+    }
+
+    async fn is_durable(&self) -> StorageResult<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry_markers_sort_by_expiry_and_name_their_object() {
+        let early = expiry_marker_key("secrets/", "secrets/tombstones/abc", 999);
+        let late = expiry_marker_key("secrets/", "secrets/abc", 1_000);
+        assert_eq!(early, "secrets/expiry/00000000000000000999/tombstones/abc");
+        assert!(early < late, "markers must list in expiry order");
+
+        assert_eq!(
+            parse_expiry_marker("secrets/", &early),
+            Some((999, "secrets/tombstones/abc".to_string()))
+        );
+        assert_eq!(
+            parse_expiry_marker("secrets/", &late),
+            Some((1_000, "secrets/abc".to_string()))
+        );
+        assert_eq!(parse_expiry_marker("secrets/", "secrets/abc"), None);
+    }
+}
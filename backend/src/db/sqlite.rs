@@ -0,0 +1,491 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteExecutor, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+};
+use time::OffsetDateTime;
+
+use super::{PoolStats, SecretStore, StorageError, StorageResult, sqlx_pool_stats};
+use crate::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+
+/// SQLite-backed implementation of `SecretStore` for single-node deployments.
+///
+/// Each secret is a row holding its JSON-serialized `Secret` and expiry time.
+/// Reads delete the row inside the same transaction that fetches it, and
+/// `secure_delete` is enabled so burned rows are zeroed on disk rather than
+/// left behind in free pages. The database uses a rollback journal truncated
+/// at every commit rather than a write-ahead log, which would keep copies of
+/// burned rows until the next checkpoint. Expired rows that are never read are
+/// removed by the sweeper started with [`SqliteSecretStore::spawn_sweeper`].
+pub struct SqliteSecretStore {
+    /// Shared with the sweeper only through a weak reference, so dropping the
+    /// store closes the pool and stops the sweeper.
+    pool: Arc<SqlitePool>,
+    /// False for in-memory databases, which vanish with the process.
+    durable: bool,
+}
+
+impl SqliteSecretStore {
+    /// Open (creating if necessary) the database at `url` and run pending migrations.
+    ///
+    /// `url` uses the `sqlite:` scheme, e.g. `sqlite:///var/lib/cendre/secrets.db`.
+    pub async fn new(url: &str) -> StorageResult<Self> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Truncate)
+            .busy_timeout(Duration::from_secs(5))
+            .pragma("secure_delete", "ON");
+
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        let durable = !(url.contains(":memory:") || url.contains("mode=memory"));
+        Ok(Self {
+            pool: Arc::new(pool),
+            durable,
+        })
+    }
+
+    /// Start a background task that deletes expired rows every `interval`.
+    ///
+    /// The task only holds a weak reference to the store's connection pool and
+    /// exits once the store has been dropped.
+    pub fn spawn_sweeper(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::downgrade(&self.pool);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+
+                match purge_expired(&pool, OffsetDateTime::now_utc()).await {
+                    Ok(0) => {}
+                    Ok(purged) => {
+                        tracing::debug!(purged, "swept expired secrets from SQLite store")
+                    }
+                    Err(err) => tracing::warn!("failed to sweep SQLite store: {:?}", err),
+                }
+            }
+        })
+    }
+}
+
+async fn purge_expired(pool: &SqlitePool, now: OffsetDateTime) -> StorageResult<u64> {
+    // Secrets that ran out unread leave their tombstone in the same step.
+    let mut tx = pool.begin().await?;
+    let expired: Vec<(String, String)> =
+        sqlx::query_as("DELETE FROM secrets WHERE expires_at <= ? RETURNING id, body")
+            .bind(now.unix_timestamp())
+            .fetch_all(&mut *tx)
+            .await?;
+    for (id, body) in &expired {
+        // A record that cannot be decoded could never be served either.
+        let Ok(secret) = serde_json::from_str::<Secret>(body) else {
+            continue;
+        };
+        if let Some(tombstone) = secret.expiry_tombstone() {
+            if !tombstone.is_expired_at(now) {
+                write_tombstone(&mut *tx, id, &tombstone).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    let tombstones = sqlx::query("DELETE FROM tombstones WHERE expires_at <= ?")
+        .bind(now.unix_timestamp())
+        .execute(pool)
+        .await?;
+
+    let receipts = sqlx::query("DELETE FROM receipts WHERE expires_at <= ?")
+        .bind(now.unix_timestamp())
+        .execute(pool)
+        .await?;
+
+    let idempotency_records = sqlx::query("DELETE FROM idempotency_records WHERE expires_at <= ?")
+        .bind(now.unix_timestamp())
+        .execute(pool)
+        .await?;
+
+    Ok(expired.len() as u64
+        + tombstones.rows_affected()
+        + receipts.rows_affected()
+        + idempotency_records.rows_affected())
+}
+
+/// Save `tombstone` for the secret `id`, replacing any earlier one.
+async fn write_tombstone<'e>(
+    executor: impl SqliteExecutor<'e>,
+    id: &str,
+    tombstone: &Tombstone,
+) -> StorageResult<()> {
+    let body = serde_json::to_string(tombstone)?;
+
+    sqlx::query(
+        "INSERT INTO tombstones (id, expires_at, body) VALUES (?, ?, ?) \
+         ON CONFLICT (id) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+    )
+    .bind(id)
+    .bind(tombstone.expires_at.unix_timestamp())
+    .bind(body)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl SecretStore for SqliteSecretStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        let body = serde_json::to_string(&secret)?;
+
+        sqlx::query(
+            "INSERT INTO secrets (id, expires_at, body, remaining_views) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&secret.id)
+        .bind(secret.expires_at().unix_timestamp())
+        .bind(body)
+        .bind(secret.remaining_views as i32)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(secret)
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+
+        // SQLite has no row locks. In this deferred transaction the UPDATE
+        // takes the database-wide write lock, so concurrent readers of any
+        // secret, like every other writer, wait behind it for up to
+        // `busy_timeout` and then see the decremented (or deleted) row.
+        let row: Option<(String, i32)> = sqlx::query_as(
+            "UPDATE secrets SET remaining_views = remaining_views - 1 \
+             WHERE id = ? AND expires_at > ? AND remaining_views > 0 \
+             RETURNING body, remaining_views",
+        )
+        .bind(id)
+        .bind(now.unix_timestamp())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((body, remaining_views)) = row else {
+            return Ok(None);
+        };
+
+        let mut secret: Secret = serde_json::from_str(&body)?;
+        secret.mark_read(now);
+        secret.remaining_views = remaining_views.max(0) as u32;
+
+        if remaining_views <= 0 {
+            sqlx::query("DELETE FROM secrets WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            if let Some(tombstone) = secret.read_tombstone() {
+                write_tombstone(&mut *tx, id, &tombstone).await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(Some(secret))
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        let now = OffsetDateTime::now_utc();
+
+        let row: Option<(String, i32, i32)> = sqlx::query_as(
+            "SELECT body, remaining_views, failed_pin_attempts FROM secrets \
+             WHERE id = ? AND expires_at > ?",
+        )
+        .bind(id)
+        .bind(now.unix_timestamp())
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        let Some((body, remaining_views, failed_pin_attempts)) = row else {
+            return Ok(None);
+        };
+
+        let mut secret: Secret = serde_json::from_str(&body)?;
+        secret.remaining_views = remaining_views.max(0) as u32;
+        secret.failed_pin_attempts = failed_pin_attempts.max(0) as u32;
+        Ok(Some(secret))
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+
+        let failures: Option<i32> = sqlx::query_scalar(
+            "UPDATE secrets SET failed_pin_attempts = failed_pin_attempts + 1 \
+             WHERE id = ? AND expires_at > ? \
+             RETURNING failed_pin_attempts",
+        )
+        .bind(id)
+        .bind(now.unix_timestamp())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(failures) = failures else {
+            return Ok(None);
+        };
+
+        let attempts_left = max_attempts.saturating_sub(failures.max(0) as u32);
+        if attempts_left == 0 {
+            sqlx::query("DELETE FROM secrets WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(Some(attempts_left))
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        let result = sqlx::query("DELETE FROM secrets WHERE id = ? AND expires_at > ?")
+            .bind(id)
+            .bind(OffsetDateTime::now_utc().unix_timestamp())
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        let Some(mut secret) = self.peek_secret(id).await? else {
+            return Ok(None);
+        };
+        secret.ttl_secs = ttl_secs;
+
+        // Only the view and PIN counters change after creation, and they live
+        // in their own columns, so rewriting the body cannot lose an update.
+        // A row deleted since the peek is simply not matched.
+        let row: Option<(i32, i32)> = sqlx::query_as(
+            "UPDATE secrets SET body = ?, expires_at = ? \
+             WHERE id = ? AND expires_at > ? \
+             RETURNING remaining_views, failed_pin_attempts",
+        )
+        .bind(serde_json::to_string(&secret)?)
+        .bind(secret.expires_at().unix_timestamp())
+        .bind(id)
+        .bind(OffsetDateTime::now_utc().unix_timestamp())
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(row.map(|(remaining_views, failed_pin_attempts)| {
+            secret.remaining_views = remaining_views.max(0) as u32;
+            secret.failed_pin_attempts = failed_pin_attempts.max(0) as u32;
+            secret
+        }))
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        write_tombstone(&*self.pool, id, tombstone).await
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        let now = OffsetDateTime::now_utc();
+
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM tombstones WHERE id = ? AND expires_at > ?")
+                .bind(id)
+                .bind(now.unix_timestamp())
+                .fetch_optional(&*self.pool)
+                .await?;
+        if let Some(body) = body {
+            return Ok(Some(serde_json::from_str(&body)?));
+        }
+
+        // Expired secrets stay in the table until the sweeper gets to them.
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM secrets WHERE id = ? AND expires_at <= ?")
+                .bind(id)
+                .bind(now.unix_timestamp())
+                .fetch_optional(&*self.pool)
+                .await?;
+        let secret: Option<Secret> = body.map(|body| serde_json::from_str(&body)).transpose()?;
+
+        Ok(secret
+            .and_then(|secret| secret.expiry_tombstone())
+            .filter(|tombstone| !tombstone.is_expired_at(now)))
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        let body = serde_json::to_string(receipt)?;
+
+        sqlx::query(
+            "INSERT INTO receipts (id, expires_at, body) VALUES (?, ?, ?) \
+             ON CONFLICT (id) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+        )
+        .bind(&receipt.id)
+        .bind(receipt.expires_at.unix_timestamp())
+        .bind(body)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        let body: Option<String> =
+            sqlx::query_scalar("SELECT body FROM receipts WHERE id = ? AND expires_at > ?")
+                .bind(id)
+                .bind(OffsetDateTime::now_utc().unix_timestamp())
+                .fetch_optional(&*self.pool)
+                .await?;
+
+        Ok(body.map(|body| serde_json::from_str(&body)).transpose()?)
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        sqlx::query("DELETE FROM receipts WHERE id = ?")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        let body = serde_json::to_string(record)?;
+
+        // An expired record may be replaced; a live one only ever reported.
+        // Should the live one vanish between the two statements, try again.
+        loop {
+            let inserted = sqlx::query(
+                "INSERT INTO idempotency_records (key, expires_at, body) VALUES (?, ?, ?) \
+                 ON CONFLICT (key) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body \
+                 WHERE idempotency_records.expires_at <= ?",
+            )
+            .bind(key)
+            .bind(record.expires_at.unix_timestamp())
+            .bind(&body)
+            .bind(OffsetDateTime::now_utc().unix_timestamp())
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+            if inserted > 0 {
+                return Ok(None);
+            }
+
+            if let Some(existing) = self.get_idempotency_record(key).await? {
+                return Ok(Some(existing));
+            }
+        }
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        let body: Option<String> = sqlx::query_scalar(
+            "SELECT body FROM idempotency_records WHERE key = ? AND expires_at > ?",
+        )
+        .bind(key)
+        .bind(OffsetDateTime::now_utc().unix_timestamp())
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(body.map(|body| serde_json::from_str(&body)).transpose()?)
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        let body = serde_json::to_string(record)?;
+
+        sqlx::query(
+            "INSERT INTO idempotency_records (key, expires_at, body) VALUES (?, ?, ?) \
+             ON CONFLICT (key) DO UPDATE SET expires_at = excluded.expires_at, body = excluded.body",
+        )
+        .bind(key)
+        .bind(record.expires_at.unix_timestamp())
+        .bind(body)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        sqlx::query("SELECT 1").execute(&*self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn is_durable(&self) -> StorageResult<bool> {
+        Ok(self.durable)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(sqlx_pool_stats(&*self.pool))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connections_enable_secure_delete() {
+        let path = std::env::temp_dir().join(format!("cendre-unit-{}.db", uuid::Uuid::new_v4()));
+        let store = SqliteSecretStore::new(&format!("sqlite://{}", path.display()))
+            .await
+            .expect("SqliteSecretStore should open a fresh database");
+
+        let enabled: i64 = sqlx::query_scalar("PRAGMA secure_delete")
+            .fetch_one(&*store.pool)
+            .await
+            .expect("PRAGMA secure_delete should be queryable");
+        assert_eq!(enabled, 1, "burned rows must be overwritten on disk");
+
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&*store.pool)
+            .await
+            .expect("PRAGMA journal_mode should be queryable");
+        assert_eq!(journal_mode, "truncate", "a WAL would keep burned rows");
+
+        let secret = store
+            .store_secret("burn-me-ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect("store_secret should succeed");
+        assert!(
+            store
+                .burn_secret(&secret.id)
+                .await
+                .expect("burn should succeed")
+        );
+
+        for suffix in ["", "-journal", "-wal"] {
+            let bytes = std::fs::read(format!("{}{}", path.display(), suffix)).unwrap_or_default();
+            assert!(
+                !bytes
+                    .windows(18)
+                    .any(|window| window == b"burn-me-ciphertext"),
+                "burned ciphertext left in {suffix:?}"
+            );
+        }
+
+        store.pool.close().await;
+        for suffix in ["", "-journal"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -1,5 +1,9 @@
 pub mod db;
 pub mod models;
+pub mod pin;
+pub mod token;
+pub mod ttl;
+pub mod user_agent;
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -10,21 +14,163 @@ use axum::{
     Json, Router,
     body::Body,
     extract::{Path, State},
-    http::{HeaderMap, HeaderValue, Request, StatusCode},
+    http::{HeaderMap, HeaderValue, Request, StatusCode, header},
     middleware::Next,
     response::IntoResponse,
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::db::{InMemorySecretStore, RedisSecretStore, SecretStore, StorageError};
+use crate::db::{
+    FileSecretStore, InMemorySecretStore, InMemoryStoreLimits, PoolStats, PostgresSecretStore,
+    RedisClientCert, RedisPoolConfig, RedisSecretStore, RedisSecurity, RedisTopology,
+    S3SecretStore, S3StoreConfig, SecretStore, SqliteSecretStore, StorageError, StorageResult,
+    StoreRegistry,
+};
+use crate::models::{
+    IdempotencyRecord, Receipt, ReceiptStatus, Secret, SecretMetadata, Tombstone, TombstoneReason,
+    TtlStart,
+};
+use crate::ttl::TtlPolicy;
 
 type SharedSecretStore = Arc<dyn SecretStore>;
 
+/// Request header carrying the PIN for a PIN-protected secret.
+const PIN_HEADER: &str = "x-secret-pin";
+
+/// Request header carrying the claim token handed out by a grace-mode read.
+const CLAIM_TOKEN_HEADER: &str = "x-claim-token";
+
+/// Request header that makes retrying `POST /api/secrets` safe.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest `Idempotency-Key` accepted.
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// API behaviour that does not depend on the storage backend.
+#[derive(Clone, Debug)]
+pub struct ApiConfig {
+    /// Wrong PINs allowed before a PIN-protected secret is burned.
+    pub pin_max_attempts: u32,
+    /// Let `GET /api/secret/:id` consume the secret, as it did before
+    /// `POST /api/secret/:id/reveal` existed. Off by default, because link
+    /// previewers and mail scanners fetch every URL they come across.
+    pub legacy_consuming_get: bool,
+    /// How long a read, revoked or expired secret keeps answering `410 Gone`
+    /// before it becomes indistinguishable from one that never existed. Zero
+    /// turns tombstones off.
+    pub tombstone_ttl: Duration,
+    /// How long a read receipt outlives its secret's expiry. Zero turns
+    /// receipts off.
+    pub receipt_ttl: Duration,
+    /// Record the reader's browser family (never the full `User-Agent`) on
+    /// read receipts.
+    pub receipt_client_hints: bool,
+    /// The TTLs secrets may be given, and the ones offered to clients.
+    pub ttl_policy: TtlPolicy,
+    /// How long a create request's `Idempotency-Key` keeps answering retries
+    /// with the same secret. Zero ignores the header.
+    pub idempotency_ttl: Duration,
+    /// Bearer token that unlocks connection pool statistics on
+    /// `GET /health/store`. Without it, only the status is reported.
+    pub health_token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            pin_max_attempts: 5,
+            legacy_consuming_get: false,
+            tombstone_ttl: Duration::from_secs(24 * 60 * 60),
+            receipt_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            receipt_client_hints: false,
+            ttl_policy: TtlPolicy::default(),
+            idempotency_ttl: Duration::from_secs(24 * 60 * 60),
+            health_token: None,
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Read `PIN_MAX_ATTEMPTS`, `LEGACY_CONSUMING_GET`, `TOMBSTONE_TTL_SECS`,
+    /// `RECEIPT_TTL_SECS`, `RECEIPT_CLIENT_HINTS`, `IDEMPOTENCY_TTL_SECS`,
+    /// `HEALTH_TOKEN` and the `TTL_*` policy variables, falling back to the
+    /// defaults for anything unset or invalid.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            pin_max_attempts: env_parse("PIN_MAX_ATTEMPTS")
+                .filter(|attempts| *attempts > 0)
+                .unwrap_or(defaults.pin_max_attempts),
+            legacy_consuming_get: env_parse("LEGACY_CONSUMING_GET")
+                .unwrap_or(defaults.legacy_consuming_get),
+            tombstone_ttl: env_parse("TOMBSTONE_TTL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.tombstone_ttl),
+            receipt_ttl: env_parse("RECEIPT_TTL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.receipt_ttl),
+            receipt_client_hints: env_parse("RECEIPT_CLIENT_HINTS")
+                .unwrap_or(defaults.receipt_client_hints),
+            ttl_policy: ttl_policy_from_env(defaults.ttl_policy),
+            idempotency_ttl: env_parse("IDEMPOTENCY_TTL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.idempotency_ttl),
+            health_token: std::env::var("HEALTH_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty()),
+        }
+    }
+}
+
+/// Read `TTL_MIN_SECS`, `TTL_MAX_SECS`, `TTL_LONG_MAX_SECS`,
+/// `TTL_DEFAULT_SECS` and `TTL_PRESETS` over `defaults`. Default presets
+/// outside the configured bounds are dropped, but a policy that otherwise
+/// does not hang together is ignored as a whole, since mixing it with the
+/// defaults could allow far more than meant.
+fn ttl_policy_from_env(defaults: TtlPolicy) -> TtlPolicy {
+    let presets = match std::env::var("TTL_PRESETS") {
+        Ok(raw) if !raw.trim().is_empty() => match ttl::parse_presets(&raw) {
+            Ok(presets) => Some(presets),
+            Err(err) => {
+                tracing::warn!("Ignoring TTL policy from the environment: {}", err);
+                return defaults;
+            }
+        },
+        _ => None,
+    };
+
+    let max_secs = env_parse("TTL_MAX_SECS").unwrap_or(defaults.max_secs);
+    let mut policy = TtlPolicy {
+        min_secs: env_parse("TTL_MIN_SECS").unwrap_or(defaults.min_secs),
+        max_secs,
+        long_max_secs: env_parse("TTL_LONG_MAX_SECS")
+            .unwrap_or(defaults.long_max_secs.max(max_secs)),
+        default_secs: env_parse("TTL_DEFAULT_SECS").unwrap_or(defaults.default_secs),
+        presets: Vec::new(),
+    };
+    policy.presets = presets.unwrap_or_else(|| {
+        let mut presets = defaults.presets.clone();
+        presets.retain(|preset| policy.allows(preset.ttl_secs, true));
+        presets
+    });
+
+    match policy.validate() {
+        Ok(()) => policy,
+        Err(err) => {
+            tracing::warn!("Ignoring TTL policy from the environment: {}", err);
+            defaults
+        }
+    }
+}
+
 #[derive(Clone)]
 struct AppState {
     store: SharedSecretStore,
+    config: ApiConfig,
 }
 
 #[derive(Clone)]
@@ -77,19 +223,111 @@ impl RateLimiter {
 /// This is primarily intended for tests and local development where a Redis
 /// instance is not required.
 pub fn app_router_with_in_memory_store() -> Router {
-    let state = AppState {
-        store: Arc::new(InMemorySecretStore::new()),
-    };
-    app_router_with_state(state)
+    app_router_with_store(Arc::new(InMemorySecretStore::new()))
+}
+
+/// Build an `axum::Router` instance backed by the provided `SecretStore`.
+///
+/// Useful for tests and embedders that want to drive the API against a specific
+/// storage backend.
+pub fn app_router_with_store(store: Arc<dyn SecretStore>) -> Router {
+    app_router_with_store_and_config(store, ApiConfig::default())
+}
+
+/// Like [`app_router_with_store`], with non-default [`ApiConfig`].
+pub fn app_router_with_store_and_config(store: Arc<dyn SecretStore>, config: ApiConfig) -> Router {
+    app_router_with_state(AppState { store, config })
 }
 
 /// Build an `axum::Router` instance using configuration from the environment.
 ///
-/// If `REDIS_URL` is set and Redis can be reached, a `RedisSecretStore` will be
-/// used. Otherwise the application will fall back to an in-memory store.
-pub async fn app_router_from_env() -> Router {
-    let state = build_state_from_env().await;
-    app_router_with_state(state)
+/// `STORE_URL` picks the backend by scheme (see [`builtin_store_registry`]).
+/// Without it, the older per-backend variables are consulted: Redis
+/// (`REDIS_URL`, `REDIS_SENTINELS` or `REDIS_CLUSTER_NODES`), then
+/// `POSTGRES_URL`, `S3_BUCKET`, `SQLITE_URL` and `FILE_STORE_DIR`. Otherwise
+/// the application will fall back to an in-memory store.
+///
+/// A backend that is configured but unreachable is retried for a while and
+/// then, outside strict mode, logged and replaced by the in-memory store.
+/// Invalid settings ([`StorageError::Config`]) and strict-mode failures are
+/// returned so the caller can refuse to start.
+pub async fn app_router_from_env() -> Result<Router, StorageError> {
+    app_router_from_env_with_registry(&builtin_store_registry()).await
+}
+
+/// Like [`app_router_from_env`], but resolves `STORE_URL` against `registry`.
+///
+/// Embedders that ship their own `SecretStore` can start from
+/// [`builtin_store_registry`], register an extra scheme and pass it here.
+pub async fn app_router_from_env_with_registry(
+    registry: &StoreRegistry,
+) -> Result<Router, StorageError> {
+    let state = build_state_from_env(registry).await?;
+    Ok(app_router_with_state(state))
+}
+
+/// A [`StoreRegistry`] with every backend that ships with Cendre.
+///
+/// | Scheme                   | Backend               | URL form                        |
+/// |--------------------------|-----------------------|---------------------------------|
+/// | `memory`                 | `InMemorySecretStore` | `memory://`                     |
+/// | `redis`, `rediss`        | `RedisSecretStore`    | standard Redis URL              |
+/// | `postgres`, `postgresql` | `PostgresSecretStore` | standard Postgres URL           |
+/// | `sqlite`                 | `SqliteSecretStore`   | `sqlite:///var/lib/cendre.db`   |
+/// | `file`                   | `FileSecretStore`     | `file:///var/lib/cendre/secrets`|
+/// | `s3`                     | `S3SecretStore`       | `s3://bucket[/key-prefix/]`     |
+///
+/// Tuning that has no natural place in a URL (pool sizes, TLS material,
+/// credentials, sweep intervals) still comes from the backend's own
+/// environment variables.
+pub fn builtin_store_registry() -> StoreRegistry {
+    let mut registry = StoreRegistry::new();
+
+    registry.register("memory", |_url: String| async move {
+        tracing::info!("Using InMemorySecretStore as backing store");
+        Ok(Arc::new(in_memory_store_from_env()) as SharedSecretStore)
+    });
+    for scheme in ["redis", "rediss"] {
+        registry.register(scheme, |url: String| async move {
+            open_redis_store(RedisTopology::Standalone { url }).await
+        });
+    }
+    for scheme in ["postgres", "postgresql"] {
+        registry.register(scheme, |url: String| async move {
+            open_postgres_store(&url).await
+        });
+    }
+    registry.register("sqlite", |url: String| async move {
+        open_sqlite_store(&url).await
+    });
+    registry.register("file", |url: String| async move {
+        // Accept both `file:///abs/path` and `file://relative/path`.
+        let dir = url
+            .strip_prefix("file://")
+            .or_else(|| url.strip_prefix("file:"))
+            .unwrap_or(&url);
+        open_file_store(dir)
+    });
+    registry.register("s3", |url: String| async move {
+        let location = url.split_once("://").map_or("", |(_, rest)| rest);
+        let (bucket, key_prefix) = match location.split_once('/') {
+            Some((bucket, prefix)) => (bucket, Some(prefix).filter(|p| !p.is_empty())),
+            None => (location, None),
+        };
+        if bucket.is_empty() {
+            return Err(StorageError::Config(
+                "S3 store URL must name a bucket, e.g. s3://cendre-secrets".to_string(),
+            ));
+        }
+
+        let mut config = s3_config_from_env(bucket.to_string())?;
+        if let Some(key_prefix) = key_prefix {
+            config.key_prefix = key_prefix.to_string();
+        }
+        open_s3_store(config).await
+    });
+
+    registry
 }
 
 fn app_router_with_state(state: AppState) -> Router {
@@ -100,8 +338,19 @@ fn app_router_with_state(state: AppState) -> Router {
 
     Router::new()
         .route("/health", get(health_check))
+        .route("/health/store", get(store_health_check))
         .route("/api/secrets", post(create_secret))
-        .route("/api/secret/:id", get(get_secret))
+        .route("/api/ttl-policy", get(get_ttl_policy))
+        .route(
+            "/api/secret/:id",
+            get(get_secret)
+                .head(describe_secret)
+                .patch(update_secret)
+                .delete(revoke_secret),
+        )
+        .route("/api/secret/:id/meta", get(get_secret_metadata))
+        .route("/api/secret/:id/reveal", post(reveal_secret))
+        .route("/api/receipt/:id", get(get_receipt))
         .route_layer(axum::middleware::from_fn_with_state(
             rate_limiter,
             rate_limit_middleware,
@@ -109,29 +358,379 @@ fn app_router_with_state(state: AppState) -> Router {
         .with_state(state)
 }
 
-async fn build_state_from_env() -> AppState {
-    // Prefer Redis when REDIS_URL is configured; otherwise fall back to in-memory storage.
-    if let Ok(url) = std::env::var("REDIS_URL") {
-        match RedisSecretStore::new(&url).await {
-            Ok(store) => {
-                tracing::info!("Using RedisSecretStore as backing store");
-                return AppState {
-                    store: Arc::new(store),
-                };
-            }
-            Err(err) => {
-                tracing::warn!(
-                    "Failed to initialize RedisSecretStore ({}); falling back to in-memory store",
-                    format!("{:?}", err)
-                );
-            }
+async fn build_state_from_env(registry: &StoreRegistry) -> Result<AppState, StorageError> {
+    let policy = startup_policy_from_env();
+
+    let store = match retry_startup(policy.retry_window, || open_configured_store(registry)).await {
+        Ok(Some(store)) => store,
+        Ok(None) => {
+            no_store_configured(policy.strict)?;
+            Arc::new(in_memory_store_from_env())
+        }
+        Err(err) => {
+            fall_back(err, policy.strict)?;
+            Arc::new(in_memory_store_from_env())
         }
+    };
+
+    Ok(AppState {
+        store,
+        config: ApiConfig::from_env(),
+    })
+}
+
+/// Open whichever store the environment asks for, or `None` if none is configured.
+async fn open_configured_store(
+    registry: &StoreRegistry,
+) -> StorageResult<Option<SharedSecretStore>> {
+    // STORE_URL wins; otherwise prefer Redis when it is configured (see
+    // `redis_topology_from_env`), then Postgres (POSTGRES_URL), S3 (S3_BUCKET),
+    // SQLite (SQLITE_URL) and plain files (FILE_STORE_DIR).
+    let store = if let Some(url) = std::env::var("STORE_URL").ok().filter(|u| !u.is_empty()) {
+        registry.open(&url).await?
+    } else if let Some(topology) = redis_topology_from_env() {
+        open_redis_store(topology).await?
+    } else if let Ok(url) = std::env::var("POSTGRES_URL") {
+        open_postgres_store(&url).await?
+    } else if let Ok(bucket) = std::env::var("S3_BUCKET") {
+        open_s3_store(s3_config_from_env(bucket)?).await?
+    } else if let Ok(url) = std::env::var("SQLITE_URL") {
+        open_sqlite_store(&url).await?
+    } else if let Ok(dir) = std::env::var("FILE_STORE_DIR") {
+        open_file_store(&dir)?
     } else {
-        tracing::info!("REDIS_URL not set; using in-memory secret store");
+        return Ok(None);
+    };
+
+    Ok(Some(store))
+}
+
+/// How startup reacts when the configured store cannot be opened.
+struct StartupPolicy {
+    /// Refuse to start instead of degrading to the in-memory store.
+    strict: bool,
+    /// How long to keep retrying a store that is unreachable.
+    retry_window: Duration,
+}
+
+/// Strict mode defaults to on when `CENDRE_ENV=production` and can be forced
+/// either way with `STORE_STRICT`. `STORE_STARTUP_RETRY_SECS` sets the retry
+/// window, which defaults to 30 seconds in strict mode and to none otherwise.
+fn startup_policy_from_env() -> StartupPolicy {
+    let production =
+        std::env::var("CENDRE_ENV").is_ok_and(|env| env.trim().eq_ignore_ascii_case("production"));
+    let strict = env_parse("STORE_STRICT").unwrap_or(production);
+    let default_window = if strict { 30 } else { 0 };
+
+    StartupPolicy {
+        strict,
+        retry_window: Duration::from_secs(
+            env_parse("STORE_STARTUP_RETRY_SECS").unwrap_or(default_window),
+        ),
+    }
+}
+
+/// Run `attempt` until it succeeds, fails with a configuration error, or
+/// `window` runs out, backing off exponentially between tries.
+async fn retry_startup<T, F, Fut>(window: Duration, mut attempt: F) -> StorageResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = StorageResult<T>>,
+{
+    const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+    const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+    let deadline = Instant::now() + window;
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let err = match attempt().await {
+            // Bad settings will not fix themselves, so there is no point waiting.
+            Err(err @ StorageError::Config(_)) => return Err(err),
+            Err(err) => err,
+            ok => return ok,
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(err);
+        }
+
+        let delay = backoff.min(remaining);
+        tracing::warn!(
+            "Secret store unavailable ({}); retrying in {:?}",
+            format!("{:?}", err),
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+async fn open_redis_store(topology: RedisTopology) -> StorageResult<SharedSecretStore> {
+    let security = redis_security_from_env()?;
+    let store =
+        RedisSecretStore::connect(topology, security, "secret:", redis_pool_config_from_env())
+            .await?;
+
+    tracing::info!("Using RedisSecretStore as backing store");
+    Ok(Arc::new(store))
+}
+
+async fn open_postgres_store(url: &str) -> StorageResult<SharedSecretStore> {
+    let store = PostgresSecretStore::new(url).await?;
+
+    tracing::info!("Using PostgresSecretStore as backing store");
+    store.spawn_sweeper(sweep_interval_from_env("POSTGRES_SWEEP_INTERVAL_SECS"));
+    Ok(Arc::new(store))
+}
+
+async fn open_s3_store(config: S3StoreConfig) -> StorageResult<SharedSecretStore> {
+    let store = S3SecretStore::new(config).await?;
+
+    tracing::info!("Using S3SecretStore as backing store");
+    store.spawn_sweeper(sweep_interval_from_env("S3_SWEEP_INTERVAL_SECS"));
+    Ok(Arc::new(store))
+}
+
+async fn open_sqlite_store(url: &str) -> StorageResult<SharedSecretStore> {
+    let store = SqliteSecretStore::new(url).await?;
+
+    tracing::info!("Using SqliteSecretStore as backing store");
+    store.spawn_sweeper(sweep_interval_from_env("SQLITE_SWEEP_INTERVAL_SECS"));
+    Ok(Arc::new(store))
+}
+
+fn open_file_store(dir: &str) -> StorageResult<SharedSecretStore> {
+    let store = FileSecretStore::new(dir)?;
+
+    tracing::info!("Using FileSecretStore as backing store");
+    store.spawn_sweeper(sweep_interval_from_env("FILE_STORE_SWEEP_INTERVAL_SECS"));
+    Ok(Arc::new(store))
+}
+
+/// Decide what to do when a configured backend fails to initialise: invalid
+/// settings and strict mode are fatal, anything else degrades to the
+/// in-memory store.
+fn fall_back(err: StorageError, strict: bool) -> Result<(), StorageError> {
+    if strict || matches!(err, StorageError::Config(_)) {
+        return Err(err);
+    }
+
+    tracing::warn!(
+        "Failed to initialize secret store ({}); falling back to in-memory store",
+        format!("{:?}", err)
+    );
+    Ok(())
+}
+
+/// Decide what to do when no backend is configured at all: strict mode
+/// refuses to start, as it would on a failed store, unless the in-memory store
+/// is asked for explicitly with `STORE_URL=memory://`.
+fn no_store_configured(strict: bool) -> Result<(), StorageError> {
+    if strict {
+        return Err(StorageError::Config(
+            "no secret store configured; set STORE_URL (memory:// to keep secrets in memory)"
+                .to_string(),
+        ));
+    }
+
+    tracing::info!("No storage backend configured; using in-memory secret store");
+    Ok(())
+}
+
+/// Read S3 connection settings for `bucket` from the environment.
+///
+/// Credentials come from `S3_ACCESS_KEY_ID`/`S3_SECRET_ACCESS_KEY`, falling back
+/// to the standard `AWS_*` variables.
+fn s3_config_from_env(bucket: String) -> Result<S3StoreConfig, StorageError> {
+    let var = |names: &[&str]| names.iter().find_map(|name| std::env::var(name).ok());
+
+    let access_key_id = var(&["S3_ACCESS_KEY_ID", "AWS_ACCESS_KEY_ID"]).ok_or_else(|| {
+        StorageError::Config("S3_ACCESS_KEY_ID or AWS_ACCESS_KEY_ID must be set".to_string())
+    })?;
+    let secret_access_key =
+        var(&["S3_SECRET_ACCESS_KEY", "AWS_SECRET_ACCESS_KEY"]).ok_or_else(|| {
+            StorageError::Config(
+                "S3_SECRET_ACCESS_KEY or AWS_SECRET_ACCESS_KEY must be set".to_string(),
+            )
+        })?;
+    let endpoint = var(&["S3_ENDPOINT"]);
+
+    Ok(S3StoreConfig {
+        // Custom endpoints are almost always MinIO-style, which needs path-style URLs.
+        force_path_style: env_parse("S3_FORCE_PATH_STYLE").unwrap_or(endpoint.is_some()),
+        endpoint,
+        bucket,
+        region: var(&["S3_REGION", "AWS_REGION"]).unwrap_or_else(|| "us-east-1".to_string()),
+        access_key_id,
+        secret_access_key,
+        key_prefix: var(&["S3_KEY_PREFIX"]).unwrap_or_else(|| "secrets/".to_string()),
+    })
+}
+
+/// Build the in-memory fallback store, bounded and swept according to the environment.
+fn in_memory_store_from_env() -> InMemorySecretStore {
+    let limits = InMemoryStoreLimits {
+        max_entries: env_parse("MEMORY_STORE_MAX_ENTRIES").or(Some(10_000)),
+        max_bytes: env_parse("MEMORY_STORE_MAX_BYTES").or(Some(64 * 1024 * 1024)),
+    };
+
+    let store = InMemorySecretStore::with_limits(limits);
+    store.spawn_sweeper(sweep_interval_from_env("MEMORY_STORE_SWEEP_INTERVAL_SECS"));
+    store
+}
+
+/// How often a store should purge expired secrets, defaulting to once a minute.
+fn sweep_interval_from_env(name: &str) -> Duration {
+    Duration::from_secs(env_parse(name).unwrap_or(60).max(1))
+}
+
+/// Work out how to reach Redis from the environment, if it is configured at all.
+///
+/// `REDIS_SENTINELS` (with `REDIS_SENTINEL_MASTER`) selects Sentinel and
+/// `REDIS_CLUSTER_NODES` selects Redis Cluster; both take comma-separated node
+/// addresses. Otherwise `REDIS_URL` names a single server.
+fn redis_topology_from_env() -> Option<RedisTopology> {
+    if let Ok(sentinels) = std::env::var("REDIS_SENTINELS") {
+        let master_name = std::env::var("REDIS_SENTINEL_MASTER").unwrap_or_else(|_| {
+            tracing::warn!("REDIS_SENTINEL_MASTER not set; assuming \"mymaster\"");
+            "mymaster".to_string()
+        });
+
+        return Some(RedisTopology::Sentinel {
+            sentinels: redis_node_list(&sentinels),
+            master_name,
+            db: env_parse("REDIS_DB").unwrap_or(0),
+        });
+    }
+
+    if let Ok(nodes) = std::env::var("REDIS_CLUSTER_NODES") {
+        return Some(RedisTopology::Cluster {
+            nodes: redis_node_list(&nodes),
+        });
+    }
+
+    std::env::var("REDIS_URL")
+        .ok()
+        .map(|url| RedisTopology::Standalone { url })
+}
+
+/// Split a comma-separated node list, accepting bare `host:port` entries.
+fn redis_node_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|node| !node.is_empty())
+        .map(|node| {
+            if node.contains("://") {
+                node.to_string()
+            } else {
+                format!("redis://{node}")
+            }
+        })
+        .collect()
+}
+
+/// Read Redis credentials and TLS settings from the environment.
+///
+/// `REDIS_USERNAME` and `REDIS_PASSWORD` (or their `_FILE` variants) set ACL
+/// credentials. `REDIS_TLS=true` forces TLS for every topology (standalone URLs
+/// can use `rediss://` instead), `REDIS_TLS_CA_FILE` trusts a private CA and
+/// `REDIS_TLS_CERT_FILE`/`REDIS_TLS_KEY_FILE` enable mutual TLS.
+fn redis_security_from_env() -> Result<RedisSecurity, StorageError> {
+    let tls = match std::env::var("REDIS_TLS") {
+        Ok(raw) if !raw.trim().is_empty() => raw.trim().parse().map_err(|_| {
+            StorageError::Config(format!("REDIS_TLS must be true or false, got {raw:?}"))
+        })?,
+        _ => false,
+    };
+
+    let client_cert = match (
+        read_env_file("REDIS_TLS_CERT_FILE")?,
+        read_env_file("REDIS_TLS_KEY_FILE")?,
+    ) {
+        (Some(cert), Some(key)) => Some(RedisClientCert { cert, key }),
+        (None, None) => None,
+        _ => {
+            return Err(StorageError::Config(
+                "REDIS_TLS_CERT_FILE and REDIS_TLS_KEY_FILE must be set together".to_string(),
+            ));
+        }
+    };
+
+    Ok(RedisSecurity {
+        username: secret_from_env("REDIS_USERNAME")?,
+        password: secret_from_env("REDIS_PASSWORD")?,
+        tls,
+        ca_cert: read_env_file("REDIS_TLS_CA_FILE")?,
+        client_cert,
+    })
+}
+
+/// Read a credential from `NAME`, or from the file named by `NAME_FILE` so it
+/// can come from a Docker/Kubernetes secret mount instead of the environment.
+fn secret_from_env(name: &str) -> Result<Option<String>, StorageError> {
+    let file_var = format!("{name}_FILE");
+    let value = std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    match (value, read_env_file(&file_var)?) {
+        (Some(_), Some(_)) => Err(StorageError::Config(format!(
+            "only one of {name} and {file_var} may be set"
+        ))),
+        (Some(value), None) => Ok(Some(value)),
+        (None, Some(contents)) => {
+            let value = String::from_utf8(contents)
+                .map_err(|_| StorageError::Config(format!("{file_var} is not valid UTF-8")))?;
+            // Secret files usually end with a newline that is not part of the value.
+            Ok(Some(value.trim_end_matches(['\r', '\n']).to_string()))
+        }
+        (None, None) => Ok(None),
     }
+}
+
+/// Read the file named by the environment variable `name`, if it is set.
+fn read_env_file(name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+    let Some(path) = std::env::var(name).ok().filter(|p| !p.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    std::fs::read(&path)
+        .map(Some)
+        .map_err(|err| StorageError::Config(format!("cannot read {name} ({path}): {err}")))
+}
+
+/// Read Redis connection pool settings from the environment, keeping the
+/// defaults for anything unset or unparsable.
+fn redis_pool_config_from_env() -> RedisPoolConfig {
+    let defaults = RedisPoolConfig::default();
 
-    AppState {
-        store: Arc::new(InMemorySecretStore::new()),
+    RedisPoolConfig {
+        max_size: env_parse("REDIS_POOL_MAX_SIZE").unwrap_or(defaults.max_size),
+        min_idle: env_parse("REDIS_POOL_MIN_IDLE").or(defaults.min_idle),
+        idle_timeout: match env_parse::<u64>("REDIS_POOL_IDLE_TIMEOUT_SECS") {
+            // A timeout of zero disables idle reaping entirely.
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => defaults.idle_timeout,
+        },
+        connection_timeout: env_parse("REDIS_POOL_CONNECTION_TIMEOUT_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.connection_timeout),
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let raw = std::env::var(name).ok()?;
+    if raw.trim().is_empty() {
+        return None;
+    }
+
+    match raw.trim().parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid value {:?} for {}", raw, name);
+            None
+        }
     }
 }
 
@@ -205,6 +804,7 @@ async fn rate_limit_middleware(
 
         let body = Json(ErrorBody {
             error: "too many requests".to_string(),
+            ..ErrorBody::default()
         });
         let mut response = body.into_response();
         *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
@@ -219,6 +819,24 @@ async fn rate_limit_middleware(
 enum ApiError {
     BadRequest(&'static str),
     NotFound,
+    /// No receipt with this id, or it has expired.
+    ReceiptNotFound,
+    /// The secret existed but has been read, revoked or has expired.
+    Gone(Tombstone),
+    /// The secret is embargoed until `unlock_at`.
+    Locked {
+        unlock_at: time::OffsetDateTime,
+    },
+    /// A required credential (PIN or management token) was not supplied.
+    Unauthorized(&'static str),
+    /// The supplied management token does not match the secret.
+    Forbidden(&'static str),
+    /// The request contradicts an earlier one, such as a reused `Idempotency-Key`.
+    Conflict(&'static str),
+    /// The supplied PIN was wrong; the secret is burned once no attempts are left.
+    WrongPin {
+        attempts_left: u32,
+    },
     Storage(StorageError),
 }
 
@@ -228,16 +846,89 @@ impl From<StorageError> for ApiError {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Default, Serialize)]
 struct ErrorBody {
     error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attempts_left: Option<u32>,
+    /// Why a secret is gone, for `410 Gone`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<TombstoneReason>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    read_at: Option<time::OffsetDateTime>,
+    /// When an embargoed secret becomes readable, for `423 Locked`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    unlock_at: Option<time::OffsetDateTime>,
 }
 
+/// Seconds a client should wait before retrying when the store is unavailable.
+const STORE_RETRY_AFTER_SECS: u64 = 5;
+
 impl axum::response::IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
+        let mut retry_after = None;
+        let mut body = ErrorBody::default();
         let (status, message) = match self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.to_string()),
             ApiError::NotFound => (StatusCode::NOT_FOUND, "secret not found".to_string()),
+            ApiError::ReceiptNotFound => (StatusCode::NOT_FOUND, "receipt not found".to_string()),
+            ApiError::Gone(tombstone) => {
+                body.reason = Some(tombstone.reason);
+                body.read_at = tombstone.read_at;
+                (
+                    StatusCode::GONE,
+                    "secret is no longer available".to_string(),
+                )
+            }
+            ApiError::Locked { unlock_at } => {
+                body.unlock_at = Some(unlock_at);
+                (
+                    StatusCode::LOCKED,
+                    "secret is not available yet".to_string(),
+                )
+            }
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.to_string()),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.to_string()),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg.to_string()),
+            ApiError::WrongPin { attempts_left } => {
+                body.attempts_left = Some(attempts_left);
+                (StatusCode::FORBIDDEN, "incorrect pin".to_string())
+            }
+            ApiError::Storage(StorageError::CapacityExceeded(reason)) => {
+                tracing::warn!("refusing new secret: {}", reason);
+                (
+                    StatusCode::INSUFFICIENT_STORAGE,
+                    "secret store is full".to_string(),
+                )
+            }
+            ApiError::Storage(StorageError::Unavailable(reason)) => {
+                tracing::warn!("secret store unavailable: {}", reason);
+                retry_after = Some(STORE_RETRY_AFTER_SECS);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "secret store temporarily unavailable".to_string(),
+                )
+            }
+            ApiError::Storage(StorageError::Timeout(reason)) => {
+                tracing::warn!("secret store timed out: {}", reason);
+                (
+                    StatusCode::GATEWAY_TIMEOUT,
+                    "secret store timed out".to_string(),
+                )
+            }
+            ApiError::Storage(StorageError::Conflict(reason)) => {
+                tracing::warn!("secret store conflict: {}", reason);
+                (
+                    StatusCode::CONFLICT,
+                    "conflicting secret store update".to_string(),
+                )
+            }
             ApiError::Storage(err) => {
                 tracing::error!("storage error: {:?}", err);
                 (
@@ -247,24 +938,149 @@ impl axum::response::IntoResponse for ApiError {
             }
         };
 
-        let body = Json(ErrorBody { error: message });
-        let mut response = body.into_response();
+        body.error = message;
+        let mut response = Json(body).into_response();
         *response.status_mut() = status;
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
         apply_security_headers(response.headers_mut());
         response
     }
 }
 
-#[derive(Deserialize)]
+/// Serialized only to fingerprint the request for `Idempotency-Key`.
+#[derive(Deserialize, Serialize)]
 struct CreateSecretRequest {
     ciphertext: String,
     iv: String,
-    ttl_secs: u32,
+    /// How long the secret lives; the policy default when neither this nor
+    /// `expires_at` is given.
+    #[serde(default)]
+    ttl_secs: Option<u32>,
+    /// When the secret should expire, instead of `ttl_secs`.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    expires_at: Option<time::OffsetDateTime>,
+    /// How many times the secret may be read before it is deleted.
+    #[serde(default = "default_max_views")]
+    max_views: u32,
+    /// Optional PIN the recipient must present; only its hash is stored.
+    #[serde(default)]
+    pin: Option<String>,
+    /// Keep the secret unreadable until this instant.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    not_before: Option<time::OffsetDateTime>,
+    /// Whether `ttl_secs` counts from creation (the default) or from `not_before`.
+    #[serde(default)]
+    ttl_start: TtlStart,
+    /// Let the first reader fetch the secret again for this many seconds.
+    #[serde(default)]
+    grace_secs: Option<u32>,
+}
+
+fn default_max_views() -> u32 {
+    1
+}
+
+/// Upper bound on `max_views`; multi-view secrets are meant for small groups.
+const MAX_VIEWS_LIMIT: u32 = 100;
+
+/// How far ahead `not_before` may be set.
+const MAX_EMBARGO: time::Duration = time::Duration::days(30);
+
+/// Upper bound on `grace_secs`; the window only has to cover a reload.
+const MAX_GRACE_SECS: u32 = 300;
+
+/// Keep TTL within the range the server is configured for. Whether the
+/// long tier is available is up to [`check_lifetime`].
+fn check_ttl_secs(state: &AppState, ttl_secs: u32) -> Result<(), ApiError> {
+    if !state.config.ttl_policy.allows(ttl_secs, true) {
+        return Err(ApiError::BadRequest(
+            "ttl_secs is outside the range allowed by GET /api/ttl-policy",
+        ));
+    }
+    Ok(())
+}
+
+/// Keep secrets that live into the long tier to stores that will still have
+/// them after a restart.
+///
+/// What counts is how long `secret` lives from `now`, which an embargo with
+/// `ttl_start=not_before` stretches well past `ttl_secs`.
+async fn check_lifetime(
+    state: &AppState,
+    secret: &Secret,
+    now: time::OffsetDateTime,
+) -> Result<(), ApiError> {
+    // Float-to-int casts saturate, so a lifetime past u32::MAX stays long.
+    let lifetime_secs = (secret.expires_at() - now).as_seconds_f64().ceil() as u32;
+    if state.config.ttl_policy.is_long(lifetime_secs) && !state.store.is_durable().await? {
+        return Err(ApiError::BadRequest(
+            "secret would live past max_secs, which needs a durable store this server does not use",
+        ));
+    }
+    Ok(())
+}
+
+/// Work out the TTL from `ttl_secs` or `expires_at`, whichever the client
+/// sent. An absolute expiry is rounded up to whole seconds from the instant
+/// the TTL starts counting. The result still has to pass [`check_ttl_secs`].
+fn resolve_ttl_secs(
+    policy: &TtlPolicy,
+    payload: &CreateSecretRequest,
+    now: time::OffsetDateTime,
+) -> Result<u32, ApiError> {
+    let ttl_secs = match (payload.ttl_secs, payload.expires_at) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::BadRequest(
+                "send either ttl_secs or expires_at, not both",
+            ));
+        }
+        (Some(ttl_secs), None) => ttl_secs,
+        (None, Some(expires_at)) => {
+            let start = match (payload.ttl_start, payload.not_before) {
+                (TtlStart::NotBefore, Some(not_before)) => not_before,
+                _ => now,
+            };
+            let secs = (expires_at - start).as_seconds_f64().ceil();
+            if secs < 1.0 {
+                return Err(ApiError::BadRequest(
+                    "expires_at must be after the secret's TTL starts counting",
+                ));
+            }
+            secs.min(f64::from(u32::MAX)) as u32
+        }
+        (None, None) => policy.default_secs,
+    };
+    Ok(ttl_secs)
 }
 
 #[derive(Serialize)]
 struct CreateSecretResponse {
     id: String,
+    /// Lets the sender revoke the secret; only its hash is stored.
+    management_token: String,
+    /// Lets the sender find out whether the secret was read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receipt_id: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+struct UpdateSecretRequest {
+    /// Replaces the secret's TTL, still counted from the same start.
+    ttl_secs: u32,
+}
+
+#[derive(Serialize)]
+struct UpdateSecretResponse {
+    id: String,
+    ttl_secs: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: time::OffsetDateTime,
 }
 
 #[derive(Serialize)]
@@ -273,63 +1089,871 @@ struct SecretResponse {
     ciphertext: String,
     iv: String,
     ttl_secs: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: time::OffsetDateTime,
+    /// Reads left after this one; zero means the secret has been deleted.
+    remaining_views: u32,
+    /// For grace-mode secrets, lets this reader fetch the secret again until
+    /// `claim_expires_at` by sending it in `X-Claim-Token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    claim_token: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    claim_expires_at: Option<time::OffsetDateTime>,
+}
+
+impl From<Secret> for SecretResponse {
+    fn from(secret: Secret) -> Self {
+        Self {
+            expires_at: secret.expires_at(),
+            id: secret.id,
+            ciphertext: secret.ciphertext,
+            iv: secret.iv,
+            ttl_secs: secret.ttl_secs,
+            remaining_views: secret.remaining_views,
+            claim_token: None,
+            claim_expires_at: None,
+        }
+    }
+}
+
+/// Everything about a secret that is safe to show without revealing it.
+#[derive(Serialize)]
+struct SecretMetadataResponse {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: time::OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: time::OffsetDateTime,
+    /// Length of the encoded ciphertext, in bytes.
+    ciphertext_size: usize,
+    remaining_views: u32,
+    pin_required: bool,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    not_before: Option<time::OffsetDateTime>,
+}
+
+impl From<SecretMetadata> for SecretMetadataResponse {
+    fn from(metadata: SecretMetadata) -> Self {
+        Self {
+            id: metadata.id,
+            created_at: metadata.created_at,
+            expires_at: metadata.expires_at,
+            ciphertext_size: metadata.ciphertext_len,
+            remaining_views: metadata.remaining_views,
+            pin_required: metadata.pin_required,
+            not_before: metadata.not_before,
+        }
+    }
+}
+
+/// A read receipt, as shown to the sender. Never includes the secret itself.
+#[derive(Serialize)]
+struct ReceiptResponse {
+    status: ReceiptStatus,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    read_at: Option<time::OffsetDateTime>,
+    /// Browser family of the first reader, when client hints are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_hint: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    secret_expires_at: time::OffsetDateTime,
+}
+
+#[derive(Serialize)]
+struct TtlPolicyResponse {
+    #[serde(flatten)]
+    policy: TtlPolicy,
+    /// Whether TTLs above `max_secs` are accepted, which needs a durable store.
+    long_tier_available: bool,
+}
+
+#[derive(Serialize)]
+struct StoreHealthResponse {
+    status: &'static str,
+    /// Only shown to callers presenting [`ApiConfig::health_token`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<PoolStats>,
 }
 
 async fn health_check() -> ApiResponse<&'static str> {
     ApiResponse("ok")
 }
 
+/// The TTL bounds, default and presets, so clients can offer what the
+/// server will accept.
+async fn get_ttl_policy(
+    State(state): State<AppState>,
+) -> Result<ApiResponse<Json<TtlPolicyResponse>>, ApiError> {
+    let policy = state.config.ttl_policy;
+    let long_tier_available =
+        policy.long_max_secs > policy.max_secs && state.store.is_durable().await?;
+
+    Ok(ApiResponse(Json(TtlPolicyResponse {
+        policy,
+        long_tier_available,
+    })))
+}
+
+/// Whether the store can be reached. Pool statistics describe the
+/// deployment, so they are left out unless the request carries the
+/// configured health token.
+async fn store_health_check(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResponse<(StatusCode, Json<StoreHealthResponse>)> {
+    let (status_code, status) = match state.store.ping().await {
+        Ok(()) => (StatusCode::OK, "ok"),
+        Err(err) => {
+            tracing::warn!("store health check failed: {:?}", err);
+            (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+        }
+    };
+
+    let authorized = match (&state.config.health_token, bearer_token(&headers)) {
+        (Some(expected), Some(token)) => bool::from(token.as_bytes().ct_eq(expected.as_bytes())),
+        _ => false,
+    };
+
+    ApiResponse((
+        status_code,
+        Json(StoreHealthResponse {
+            status,
+            pool: authorized.then(|| state.store.pool_stats()).flatten(),
+        }),
+    ))
+}
+
 async fn create_secret(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<CreateSecretRequest>,
 ) -> Result<ApiResponse<Json<CreateSecretResponse>>, ApiError> {
+    let idempotency = idempotency_of(&state, &headers, &payload)?;
+    if let Some(idempotency) = &idempotency {
+        if let Some(record) = state
+            .store
+            .get_idempotency_record(&idempotency.store_key)
+            .await?
+        {
+            return replay_create(record, idempotency);
+        }
+    }
+
     if payload.ciphertext.trim().is_empty() || payload.iv.trim().is_empty() {
         return Err(ApiError::BadRequest(
             "ciphertext and iv must be non-empty strings",
         ));
     }
 
-    // Keep TTL within a sane range (1 second up to 24 hours).
-    if payload.ttl_secs == 0 || payload.ttl_secs > 24 * 60 * 60 {
-        return Err(ApiError::BadRequest(
-            "ttl_secs must be between 1 and 86400 seconds",
-        ));
+    let ttl_secs = resolve_ttl_secs(
+        &state.config.ttl_policy,
+        &payload,
+        time::OffsetDateTime::now_utc(),
+    )?;
+    check_ttl_secs(&state, ttl_secs)?;
+
+    if payload.max_views == 0 || payload.max_views > MAX_VIEWS_LIMIT {
+        return Err(ApiError::BadRequest("max_views must be between 1 and 100"));
     }
 
-    let secret = state
-        .store
-        .store_secret(payload.ciphertext, payload.iv, payload.ttl_secs)
-        .await?;
+    if let Some(grace_secs) = payload.grace_secs {
+        if grace_secs == 0 || grace_secs > MAX_GRACE_SECS {
+            return Err(ApiError::BadRequest(
+                "grace_secs must be between 1 and 300 seconds",
+            ));
+        }
+        if payload.max_views != 1 {
+            return Err(ApiError::BadRequest(
+                "grace_secs is only supported for single-view secrets",
+            ));
+        }
+    }
+
+    let pin_hash = match payload.pin {
+        Some(pin) => {
+            let len = pin.chars().count();
+            if !(pin::MIN_PIN_LEN..=pin::MAX_PIN_LEN).contains(&len) {
+                return Err(ApiError::BadRequest(
+                    "pin must be between 4 and 64 characters",
+                ));
+            }
+            Some(hash_pin_blocking(pin).await?)
+        }
+        None => None,
+    };
+
+    let management_token = token::generate_token();
+    let mut secret = Secret::new(payload.ciphertext, payload.iv, ttl_secs)
+        .with_max_views(payload.max_views)
+        .with_management_token_hash(token::hash_token(&management_token));
+    if !state.config.tombstone_ttl.is_zero() {
+        let tombstone_secs =
+            u32::try_from(state.config.tombstone_ttl.as_secs()).unwrap_or(u32::MAX);
+        secret = secret.with_tombstone_secs(tombstone_secs);
+    }
+    if let Some(pin_hash) = pin_hash {
+        secret = secret.with_pin_hash(pin_hash);
+    }
+    if let Some(grace_secs) = payload.grace_secs {
+        secret = secret.with_grace_secs(grace_secs);
+    }
+    if let Some(not_before) = payload.not_before {
+        if not_before > secret.created_at + MAX_EMBARGO {
+            return Err(ApiError::BadRequest(
+                "not_before must be at most 30 days ahead",
+            ));
+        }
+        secret = secret.with_not_before(not_before, payload.ttl_start);
+        if secret.is_expired_at(not_before) {
+            return Err(ApiError::BadRequest(
+                "secret would expire before not_before; raise ttl_secs or count it from not_before",
+            ));
+        }
+    }
+    check_lifetime(&state, &secret, secret.created_at).await?;
+
+    // Saved before the secret, so that a secret is never readable without
+    // the receipt it reports to.
+    if !state.config.receipt_ttl.is_zero() {
+        let receipt = Receipt::new(
+            token::generate_token(),
+            &secret,
+            secret.expires_at() + state.config.receipt_ttl,
+        );
+        state.store.put_receipt(&receipt).await?;
+        secret = secret.with_receipt_id(receipt.id);
+    }
+    if let Some(idempotency) = &idempotency {
+        secret = secret.with_idempotency_key(idempotency.store_key.clone());
+    }
+    let secret = state.store.insert_secret(secret).await?;
+
+    if let Some(idempotency) = idempotency {
+        let record = IdempotencyRecord {
+            request_hash: idempotency.request_hash.clone(),
+            secret_id: secret.id.clone(),
+            sealed_management_token: token::seal_token(
+                &management_token,
+                &idempotency.key,
+                &secret.id,
+            ),
+            receipt_id: secret.receipt_id.clone(),
+            secret_expires_at: secret.expires_at(),
+            expires_at: time::OffsetDateTime::now_utc() + state.config.idempotency_ttl,
+        };
+        let existing = state
+            .store
+            .put_idempotency_record(&idempotency.store_key, &record)
+            .await?;
+        if let Some(existing) = existing {
+            // A concurrent retry got there first; answer with its secret, and
+            // take back ours and its receipt as if they had never been made.
+            state.store.burn_secret(&secret.id).await?;
+            if let Some(receipt_id) = &secret.receipt_id {
+                state.store.delete_receipt(receipt_id).await?;
+            }
+            return replay_create(existing, &idempotency);
+        }
+    }
 
     tracing::info!(
         secret_id = %secret.id,
         ttl_secs = secret.ttl_secs,
+        max_views = secret.remaining_views,
+        pin = secret.pin_hash.is_some(),
+        embargoed = secret.not_before.is_some(),
+        grace_secs = secret.grace_secs,
         "created secret"
     );
 
-    Ok(ApiResponse(Json(CreateSecretResponse { id: secret.id })))
+    Ok(ApiResponse(Json(CreateSecretResponse {
+        expires_at: secret.expires_at(),
+        id: secret.id,
+        management_token,
+        receipt_id: secret.receipt_id,
+    })))
 }
 
+/// Describe the secret without consuming it, so that link previewers can
+/// fetch the URL harmlessly. With [`ApiConfig::legacy_consuming_get`] set,
+/// reveal it instead.
 async fn get_secret(
+    state: State<AppState>,
+    id: Path<String>,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    if state.config.legacy_consuming_get {
+        reveal_secret(state, id, headers).await.into_response()
+    } else {
+        describe_secret(state, id).await.into_response()
+    }
+}
+
+/// Like [`get_secret_metadata`], but answers `423 Locked` while the secret is
+/// embargoed, as a read would.
+async fn describe_secret(
     State(state): State<AppState>,
     Path(id): Path<String>,
+) -> Result<ApiResponse<Json<SecretMetadataResponse>>, ApiError> {
+    let metadata = fetch_metadata(&state, &id).await?;
+    if let Some(unlock_at) = metadata.not_before {
+        if time::OffsetDateTime::now_utc() < unlock_at {
+            return Err(ApiError::Locked { unlock_at });
+        }
+    }
+
+    Ok(ApiResponse(Json(metadata.into())))
+}
+
+/// Release the ciphertext, using up one view.
+///
+/// A request carrying a claim token from an earlier grace-mode read gets the
+/// claimed copy instead.
+async fn reveal_secret(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<ApiResponse<Json<SecretResponse>>, ApiError> {
+    if let Some(claim_token) = headers.get(CLAIM_TOKEN_HEADER) {
+        let claim_token = claim_token
+            .to_str()
+            .map_err(|_| ApiError::BadRequest("malformed claim token"))?;
+        return refetch_claimed_secret(&state, &id, claim_token).await;
+    }
+
+    check_access(&state, &id, &headers).await?;
+
     let maybe_secret = state.store.get_and_delete_secret(&id).await?;
 
     match maybe_secret {
         Some(secret) => {
-            tracing::info!(secret_id = %secret.id, "read secret");
+            tracing::info!(
+                secret_id = %secret.id,
+                remaining_views = secret.remaining_views,
+                "read secret"
+            );
 
-            Ok(ApiResponse(Json(SecretResponse {
-                id: secret.id,
-                ciphertext: secret.ciphertext,
-                iv: secret.iv,
-                ttl_secs: secret.ttl_secs,
-            })))
+            // Stores leave the tombstone themselves for secrets that name one;
+            // older records are given it here.
+            if secret.remaining_views == 0 && secret.tombstone_secs.is_none() {
+                let expires_at = time::OffsetDateTime::now_utc() + state.config.tombstone_ttl;
+                leave_tombstone(&state, &secret.id, Tombstone::read(&secret, expires_at)).await;
+            }
+
+            let client_hint = state
+                .config
+                .receipt_client_hints
+                .then(|| headers.get(header::USER_AGENT)?.to_str().ok())
+                .flatten()
+                .map(|user_agent| user_agent::family(user_agent).to_string());
+            settle_receipt(
+                &state,
+                &secret,
+                ReceiptStatus::Read,
+                secret.read_at,
+                client_hint,
+            )
+            .await;
+
+            let claim = claim_secret(&state, &secret).await;
+            let mut response = SecretResponse::from(secret);
+            if let Some((claim_token, claim_expires_at)) = claim {
+                response.claim_token = Some(claim_token);
+                response.claim_expires_at = Some(claim_expires_at);
+            }
+            Ok(ApiResponse(Json(response)))
         }
         None => {
             tracing::info!(secret_id = %id, "secret not found");
-            Err(ApiError::NotFound)
+            Err(missing_secret(&state, &id).await)
+        }
+    }
+}
+
+/// For a grace-mode secret that has just been read, keep a copy that only the
+/// holder of the returned claim token can fetch, until the returned instant.
+///
+/// The read itself has already happened, so failing to keep the copy is
+/// logged and the secret is returned without a claim.
+async fn claim_secret(state: &AppState, secret: &Secret) -> Option<(String, time::OffsetDateTime)> {
+    let claim_token = token::generate_token();
+    let copy = secret.claimed_copy(token::claim_id(&secret.id, &claim_token))?;
+
+    match state.store.insert_secret(copy).await {
+        Ok(copy) => Some((claim_token, copy.expires_at())),
+        Err(err) => {
+            tracing::warn!(secret_id = %secret.id, "failed to keep claimed copy: {:?}", err);
+            None
         }
     }
 }
+
+/// Hand the claimed copy of secret `id` back to the holder of `claim_token`.
+/// Anyone else, and the holder once the window has closed, is told the
+/// secret is gone.
+async fn refetch_claimed_secret(
+    state: &AppState,
+    id: &str,
+    claim_token: &str,
+) -> Result<ApiResponse<Json<SecretResponse>>, ApiError> {
+    let claim_id = token::claim_id(id, claim_token);
+    let copy = state.store.peek_secret(&claim_id).await?;
+    let Some(copy) = copy.filter(|copy| copy.claimed) else {
+        return Err(missing_secret(state, id).await);
+    };
+
+    tracing::info!(secret_id = %id, "re-fetched claimed secret");
+    let claim_expires_at = copy.expires_at();
+    Ok(ApiResponse(Json(SecretResponse {
+        id: id.to_string(),
+        remaining_views: 0,
+        claim_token: Some(claim_token.to_string()),
+        claim_expires_at: Some(claim_expires_at),
+        ..SecretResponse::from(copy)
+    })))
+}
+
+/// Report whether a secret is still live, and when it expires, without
+/// consuming it. `HEAD` requests are answered by the same handler, minus the
+/// body.
+async fn get_secret_metadata(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<ApiResponse<Json<SecretMetadataResponse>>, ApiError> {
+    let metadata = fetch_metadata(&state, &id).await?;
+    Ok(ApiResponse(Json(metadata.into())))
+}
+
+async fn fetch_metadata(state: &AppState, id: &str) -> Result<SecretMetadata, ApiError> {
+    match state.store.secret_metadata(id).await? {
+        // Claimed copies are only served through their claim token.
+        Some(metadata) if metadata.claimed => Err(ApiError::NotFound),
+        Some(metadata) => Ok(metadata),
+        None => Err(missing_secret(state, id).await),
+    }
+}
+
+async fn revoke_secret(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<ApiResponse<StatusCode>, ApiError> {
+    let secret = authorize_sender(&state, &id, &headers).await?;
+
+    if !state.store.burn_secret(&id).await? {
+        return Err(missing_secret(&state, &id).await);
+    }
+
+    tracing::info!(secret_id = %id, "revoked secret");
+    leave_tombstone(&state, &id, tombstone_now(&state, TombstoneReason::Revoked)).await;
+    settle_receipt(&state, &secret, ReceiptStatus::Revoked, None, None).await;
+    Ok(ApiResponse(StatusCode::NO_CONTENT))
+}
+
+/// What a create request carrying an `Idempotency-Key` needs to store and
+/// replay its result.
+struct Idempotency {
+    /// Hash of the key, which the record is stored under.
+    store_key: String,
+    /// Fingerprint of the request. It is keyed with the idempotency key, so
+    /// it cannot be used to guess a PIN in the request without it.
+    request_hash: String,
+    /// The key itself, which seals the management token in the record and is
+    /// never stored.
+    key: String,
+}
+
+/// The [`Idempotency`] of a create request, unless it carries no
+/// `Idempotency-Key` or they are turned off.
+fn idempotency_of(
+    state: &AppState,
+    headers: &HeaderMap,
+    payload: &CreateSecretRequest,
+) -> Result<Option<Idempotency>, ApiError> {
+    if state.config.idempotency_ttl.is_zero() {
+        return Ok(None);
+    }
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN)
+        .ok_or(ApiError::BadRequest(
+            "Idempotency-Key must be 1 to 255 visible ASCII characters",
+        ))?;
+    let request = serde_json::to_string(payload).map_err(StorageError::from)?;
+
+    Ok(Some(Idempotency {
+        store_key: token::hash_token(key),
+        request_hash: token::hash_token(&format!("{key}\n{request}")),
+        key: key.to_string(),
+    }))
+}
+
+/// Answer a retried create request with the secret its first attempt made,
+/// provided it really is the same request.
+fn replay_create(
+    record: IdempotencyRecord,
+    idempotency: &Idempotency,
+) -> Result<ApiResponse<Json<CreateSecretResponse>>, ApiError> {
+    if record.request_hash != idempotency.request_hash {
+        return Err(ApiError::Conflict(
+            "Idempotency-Key was already used for a different request",
+        ));
+    }
+    let management_token = token::open_token(
+        &record.sealed_management_token,
+        &idempotency.key,
+        &record.secret_id,
+    )
+    .ok_or_else(|| StorageError::Serialization("idempotency record could not be opened".into()))?;
+
+    Ok(ApiResponse(Json(CreateSecretResponse {
+        id: record.secret_id,
+        management_token,
+        receipt_id: record.receipt_id,
+        expires_at: record.secret_expires_at,
+    })))
+}
+
+/// Let the sender extend or shorten a secret's lifetime. The TTL keeps
+/// counting from the same start, so the new `expires_at` is returned.
+async fn update_secret(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateSecretRequest>,
+) -> Result<ApiResponse<Json<UpdateSecretResponse>>, ApiError> {
+    let mut secret = authorize_sender(&state, &id, &headers).await?;
+
+    check_ttl_secs(&state, payload.ttl_secs)?;
+    secret.ttl_secs = payload.ttl_secs;
+    let now = time::OffsetDateTime::now_utc();
+    if secret.is_expired_at(now) {
+        return Err(ApiError::BadRequest(
+            "ttl_secs would expire the secret immediately; revoke it instead",
+        ));
+    }
+    if let Some(not_before) = secret.not_before {
+        if secret.is_expired_at(not_before) {
+            return Err(ApiError::BadRequest(
+                "ttl_secs would expire the secret before not_before, so it could never be read",
+            ));
+        }
+    }
+    check_lifetime(&state, &secret, now).await?;
+
+    let Some(secret) = state.store.set_secret_ttl(&id, payload.ttl_secs).await? else {
+        return Err(missing_secret(&state, &id).await);
+    };
+
+    // The store moves the secret's own expiry, and with it the tombstone it
+    // leaves on expiring; the receipt and idempotency record have to be told.
+    reschedule_receipt(&state, &secret).await;
+    reschedule_idempotency_record(&state, &secret).await;
+
+    tracing::info!(secret_id = %id, ttl_secs = secret.ttl_secs, "updated secret ttl");
+    Ok(ApiResponse(Json(UpdateSecretResponse {
+        id: secret.id.clone(),
+        ttl_secs: secret.ttl_secs,
+        expires_at: secret.expires_at(),
+    })))
+}
+
+/// Tell the sender what became of their secret. Only the receipt id, which
+/// is unrelated to the secret's, is needed; the ciphertext is never included.
+async fn get_receipt(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<ApiResponse<Json<ReceiptResponse>>, ApiError> {
+    let receipt = state
+        .store
+        .get_receipt(&id)
+        .await?
+        .ok_or(ApiError::ReceiptNotFound)?;
+
+    Ok(ApiResponse(Json(ReceiptResponse {
+        status: receipt.status_at(time::OffsetDateTime::now_utc()),
+        read_at: receipt.read_at,
+        client_hint: receipt.client_hint,
+        secret_expires_at: receipt.secret_expires_at,
+    })))
+}
+
+/// Look up the secret `id` on behalf of its sender, who proves themselves
+/// with the management token issued at creation.
+async fn authorize_sender(
+    state: &AppState,
+    id: &str,
+    headers: &HeaderMap,
+) -> Result<Secret, ApiError> {
+    let token = bearer_token(headers).ok_or(ApiError::Unauthorized("management token required"))?;
+
+    let Some(secret) = state.store.peek_secret(id).await? else {
+        return Err(missing_secret(state, id).await);
+    };
+    if secret.claimed {
+        return Err(ApiError::NotFound);
+    }
+    let token_hash = secret.management_token_hash.as_deref();
+    if !token_hash.is_some_and(|hash| token::verify_token(token, hash)) {
+        return Err(ApiError::Forbidden("invalid management token"));
+    }
+    Ok(secret)
+}
+
+/// The token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Refuse to release a secret that is still embargoed, or a PIN-protected
+/// one unless the request carries its PIN. Claimed copies are refused
+/// outright, as only their claim token may fetch them.
+///
+/// Each wrong PIN is counted by the store, which burns the secret once
+/// [`ApiConfig::pin_max_attempts`] have been used up. Secrets without a PIN pass through.
+async fn check_access(state: &AppState, id: &str, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(secret) = state.store.peek_secret(id).await? else {
+        return Ok(());
+    };
+    if secret.claimed {
+        return Err(ApiError::NotFound);
+    }
+    // Embargoes only ever lift, so checking ahead of the read is enough.
+    if !secret.is_available_at(time::OffsetDateTime::now_utc()) {
+        if let Some(unlock_at) = secret.not_before {
+            return Err(ApiError::Locked { unlock_at });
+        }
+    }
+    let Some(pin_hash) = secret.pin_hash.clone() else {
+        return Ok(());
+    };
+
+    let Some(pin) = headers.get(PIN_HEADER).and_then(|v| v.to_str().ok()) else {
+        return Err(ApiError::Unauthorized("pin required"));
+    };
+
+    let pin = pin.to_string();
+    let matches = tokio::task::spawn_blocking(move || pin::verify_pin(&pin, &pin_hash))
+        .await
+        .map_err(|e| StorageError::Backend(format!("pin check task failed: {e}")))?;
+    if matches {
+        return Ok(());
+    }
+
+    match state
+        .store
+        .record_failed_pin(id, state.config.pin_max_attempts)
+        .await?
+    {
+        Some(attempts_left) => {
+            tracing::info!(secret_id = %id, attempts_left, "wrong pin");
+            if attempts_left == 0 {
+                leave_tombstone(state, id, tombstone_now(state, TombstoneReason::PinLockout)).await;
+                settle_receipt(state, &secret, ReceiptStatus::PinLockout, None, None).await;
+            }
+            Err(ApiError::WrongPin { attempts_left })
+        }
+        // Burned or expired by someone else in the meantime.
+        None => Err(missing_secret(state, id).await),
+    }
+}
+
+/// The error for a secret that could not be found: `410 Gone` if it left a
+/// tombstone, `404 Not Found` otherwise.
+async fn missing_secret(state: &AppState, id: &str) -> ApiError {
+    match state.store.get_tombstone(id).await {
+        Ok(Some(tombstone)) => ApiError::Gone(tombstone),
+        Ok(None) => ApiError::NotFound,
+        Err(err) => err.into(),
+    }
+}
+
+/// A tombstone for a secret that is gone as of now.
+fn tombstone_now(state: &AppState, reason: TombstoneReason) -> Tombstone {
+    Tombstone::new(
+        reason,
+        time::OffsetDateTime::now_utc() + state.config.tombstone_ttl,
+    )
+}
+
+/// Record why the secret `id` is gone. Tombstones are a courtesy to late
+/// readers, so failing to write one is logged rather than failing the request.
+async fn leave_tombstone(state: &AppState, id: &str, tombstone: Tombstone) {
+    if state.config.tombstone_ttl.is_zero() {
+        return;
+    }
+
+    if let Err(err) = state.store.put_tombstone(id, &tombstone).await {
+        tracing::warn!(secret_id = %id, "failed to leave tombstone: {:?}", err);
+    }
+}
+
+/// Record on the secret's receipt, if it has one, what just happened to it.
+/// Like tombstones, a receipt that cannot be updated is logged rather than
+/// failing the request, which has already taken effect.
+async fn settle_receipt(
+    state: &AppState,
+    secret: &Secret,
+    status: ReceiptStatus,
+    read_at: Option<time::OffsetDateTime>,
+    client_hint: Option<String>,
+) {
+    let Some(receipt_id) = secret.receipt_id.as_deref() else {
+        return;
+    };
+
+    let result = match state.store.get_receipt(receipt_id).await {
+        Ok(Some(mut receipt)) => {
+            if receipt.settle(status, read_at, client_hint) {
+                state.store.put_receipt(&receipt).await
+            } else {
+                Ok(())
+            }
+        }
+        Ok(None) => Ok(()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        tracing::warn!(secret_id = %secret.id, "failed to update receipt: {:?}", err);
+    }
+}
+
+/// Keep the secret's receipt, if it has one, in step with a new expiry.
+async fn reschedule_receipt(state: &AppState, secret: &Secret) {
+    let Some(receipt_id) = secret.receipt_id.as_deref() else {
+        return;
+    };
+
+    let result = match state.store.get_receipt(receipt_id).await {
+        Ok(Some(mut receipt)) => {
+            receipt.secret_expires_at = secret.expires_at();
+            receipt.expires_at = secret.expires_at() + state.config.receipt_ttl;
+            state.store.put_receipt(&receipt).await
+        }
+        Ok(None) => Ok(()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        tracing::warn!(secret_id = %secret.id, "failed to update receipt: {:?}", err);
+    }
+}
+
+/// Have retries of the request that created `secret` report its current
+/// expiry, and keep answering them for as long as the secret lives.
+async fn reschedule_idempotency_record(state: &AppState, secret: &Secret) {
+    let Some(key) = secret.idempotency_key.as_deref() else {
+        return;
+    };
+
+    let result = match state.store.get_idempotency_record(key).await {
+        Ok(Some(mut record)) if record.secret_id == secret.id => {
+            record.secret_expires_at = secret.expires_at();
+            record.expires_at = record.expires_at.max(secret.expires_at());
+            state.store.replace_idempotency_record(key, &record).await
+        }
+        Ok(_) => Ok(()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        tracing::warn!(secret_id = %secret.id, "failed to update idempotency record: {:?}", err);
+    }
+}
+
+/// Hash a PIN off the async runtime; Argon2 is deliberately slow.
+async fn hash_pin_blocking(pin: String) -> Result<String, ApiError> {
+    tokio::task::spawn_blocking(move || pin::hash_pin(&pin))
+        .await
+        .map_err(|e| StorageError::Backend(format!("pin hashing task failed: {e}")))?
+        .map_err(|e| StorageError::Backend(format!("failed to hash pin: {e}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn retry_startup_retries_until_the_store_comes_up() {
+        let mut attempts = 0;
+        let result = retry_startup(Duration::from_secs(5), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(StorageError::Backend("connection refused".to_string()))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_startup_gives_up_after_the_window() {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let result: StorageResult<()> = retry_startup(Duration::from_millis(600), || {
+            attempts += 1;
+            async { Err(StorageError::Backend("connection refused".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(StorageError::Backend(_))));
+        assert!(attempts > 1);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn retry_startup_does_not_retry_config_errors() {
+        let mut attempts = 0;
+        let result: StorageResult<()> = retry_startup(Duration::from_secs(5), || {
+            attempts += 1;
+            async { Err(StorageError::Config("bad url".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(StorageError::Config(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn strict_mode_turns_any_failure_fatal() {
+        let unreachable = || StorageError::Backend("connection refused".to_string());
+
+        assert!(fall_back(unreachable(), false).is_ok());
+        assert!(fall_back(unreachable(), true).is_err());
+        assert!(fall_back(StorageError::Config("bad url".to_string()), false).is_err());
+    }
+
+    #[test]
+    fn strict_mode_requires_a_configured_store() {
+        assert!(no_store_configured(false).is_ok());
+        assert!(matches!(
+            no_store_configured(true),
+            Err(StorageError::Config(_))
+        ));
+    }
+}
@@ -8,7 +8,13 @@ use cendre_backend::{app_router_from_env, init_tracing};
 async fn main() {
     init_tracing();
 
-    let app: Router = app_router_from_env().await;
+    let app: Router = match app_router_from_env().await {
+        Ok(app) => app,
+        Err(err) => {
+            tracing::error!("failed to initialize secret store: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
     let addr: SocketAddr = std::env::var("BACKEND_BIND_ADDR")
         .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
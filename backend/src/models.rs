@@ -13,6 +13,75 @@ pub struct Secret {
     pub created_at: OffsetDateTime,
     pub ttl_secs: u32,
     pub read_at: Option<OffsetDateTime>,
+    /// Reads left before the secret is deleted. Records written before
+    /// multi-view support default to a single view.
+    #[serde(default = "default_views")]
+    pub remaining_views: u32,
+    /// Argon2 PHC string of the PIN the recipient must present, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_hash: Option<String>,
+    /// Wrong PINs entered so far.
+    #[serde(default)]
+    pub failed_pin_attempts: u32,
+    /// SHA-256 of the management token handed to the sender, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub management_token_hash: Option<String>,
+    /// Id of the [`Receipt`] the sender can query, if they were given one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt_id: Option<String>,
+    /// Key of the [`IdempotencyRecord`] that replays the secret's creation,
+    /// if it was created with an `Idempotency-Key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// The secret cannot be read before this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<OffsetDateTime>,
+    /// Whether `ttl_secs` counts from `created_at` or from `not_before`.
+    #[serde(default)]
+    pub ttl_start: TtlStart,
+    /// How long the first reader may fetch the secret again after reading it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_secs: Option<u32>,
+    /// How long a `410 Gone` outlives the secret if it runs out unread; see
+    /// [`Secret::expiry_tombstone`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tombstone_secs: Option<u32>,
+    /// Whether this is a [`Secret::claimed_copy`], which only the claim holder
+    /// may fetch.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub claimed: bool,
+}
+
+fn default_views() -> u32 {
+    1
+}
+
+/// The instant a secret's TTL counts from.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TtlStart {
+    /// From when the secret was created.
+    #[default]
+    CreatedAt,
+    /// From when its embargo lifts, so an embargoed secret gets its full TTL
+    /// once it becomes readable. The same as `CreatedAt` without one.
+    NotBefore,
+}
+
+/// What can be told about a live secret without revealing or consuming it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretMetadata {
+    pub id: String,
+    pub created_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+    /// Length of the encoded ciphertext, in bytes.
+    pub ciphertext_len: usize,
+    pub remaining_views: u32,
+    pub pin_required: bool,
+    /// When an embargoed secret becomes readable.
+    pub not_before: Option<OffsetDateTime>,
+    /// Whether this describes a [`Secret::claimed_copy`].
+    pub claimed: bool,
 }
 
 impl Secret {
@@ -29,12 +98,106 @@ impl Secret {
             created_at,
             ttl_secs,
             read_at: None,
+            remaining_views: 1,
+            pin_hash: None,
+            failed_pin_attempts: 0,
+            management_token_hash: None,
+            receipt_id: None,
+            idempotency_key: None,
+            not_before: None,
+            ttl_start: TtlStart::CreatedAt,
+            grace_secs: None,
+            tombstone_secs: None,
+            claimed: false,
         }
     }
 
+    /// Allow the secret to be read `max_views` times instead of once.
+    pub fn with_max_views(mut self, max_views: u32) -> Self {
+        self.remaining_views = max_views;
+        self
+    }
+
+    /// Keep the secret unreadable until `not_before`, with its TTL counting
+    /// from `ttl_start`.
+    pub fn with_not_before(mut self, not_before: OffsetDateTime, ttl_start: TtlStart) -> Self {
+        self.not_before = Some(not_before);
+        self.ttl_start = ttl_start;
+        self
+    }
+
+    /// Let the first reader fetch the secret again for `grace_secs` after
+    /// reading it, in case it never arrived.
+    pub fn with_grace_secs(mut self, grace_secs: u32) -> Self {
+        self.grace_secs = Some(grace_secs);
+        self
+    }
+
+    /// Leave an `Expired` tombstone for `tombstone_secs` past the secret's
+    /// expiry, should it run out unread.
+    pub fn with_tombstone_secs(mut self, tombstone_secs: u32) -> Self {
+        self.tombstone_secs = Some(tombstone_secs);
+        self
+    }
+
+    /// The tombstone a store leaves in place of this secret when it drops it
+    /// for having expired, or reports for it while it is expired but not yet
+    /// dropped.
+    pub fn expiry_tombstone(&self) -> Option<Tombstone> {
+        let tombstone_secs = self.tombstone_secs?;
+
+        Some(Tombstone::new(
+            TombstoneReason::Expired,
+            self.expires_at() + Duration::seconds(tombstone_secs as i64),
+        ))
+    }
+
+    /// The tombstone a store leaves in place of this secret once its last
+    /// view has been read, in the same step as dropping it.
+    pub fn read_tombstone(&self) -> Option<Tombstone> {
+        let tombstone_secs = self.tombstone_secs?;
+        let read_at = self.read_at.filter(|_| self.remaining_views == 0)?;
+
+        Some(Tombstone::read(
+            self,
+            read_at + Duration::seconds(tombstone_secs as i64),
+        ))
+    }
+
+    /// A copy of this secret, just read, which lives under `id` until the
+    /// grace window closes or the secret would have expired, whichever comes
+    /// first. It carries no PIN, management token or receipt: whoever can
+    /// address it has already read the secret.
+    ///
+    /// Returns `None` when the secret has no grace window, or less than a
+    /// second of it is left.
+    pub fn claimed_copy(&self, id: String) -> Option<Secret> {
+        let grace_secs = self.grace_secs?;
+        let read_at = self.read_at.unwrap_or_else(OffsetDateTime::now_utc);
+
+        let remaining_secs = (self.expires_at() - read_at).whole_seconds().max(0);
+        let ttl_secs = grace_secs.min(u32::try_from(remaining_secs).unwrap_or(u32::MAX));
+        if ttl_secs == 0 {
+            return None;
+        }
+
+        Some(Secret {
+            id,
+            created_at: read_at,
+            ttl_secs,
+            read_at: self.read_at,
+            claimed: true,
+            ..Secret::new(self.ciphertext.clone(), self.iv.clone(), ttl_secs)
+        })
+    }
+
     /// Returns the instant at which this secret should expire.
     pub fn expires_at(&self) -> OffsetDateTime {
-        self.created_at + Duration::seconds(self.ttl_secs as i64)
+        let start = match (self.ttl_start, self.not_before) {
+            (TtlStart::NotBefore, Some(not_before)) => not_before,
+            _ => self.created_at,
+        };
+        start + Duration::seconds(self.ttl_secs as i64)
     }
 
     /// Returns true if the secret should be considered expired at the given time.
@@ -42,10 +205,223 @@ impl Secret {
         now >= self.expires_at()
     }
 
+    /// Returns true if the secret's embargo, if any, has lifted at the given time.
+    pub fn is_available_at(&self, now: OffsetDateTime) -> bool {
+        self.not_before.is_none_or(|not_before| now >= not_before)
+    }
+
+    /// Require the PIN whose Argon2 hash is `pin_hash` before the secret is released.
+    pub fn with_pin_hash(mut self, pin_hash: String) -> Self {
+        self.pin_hash = Some(pin_hash);
+        self
+    }
+
+    /// Describe the secret without its ciphertext or iv.
+    pub fn metadata(&self) -> SecretMetadata {
+        SecretMetadata {
+            id: self.id.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at(),
+            ciphertext_len: self.ciphertext.len(),
+            remaining_views: self.remaining_views,
+            pin_required: self.pin_hash.is_some(),
+            not_before: self.not_before,
+            claimed: self.claimed,
+        }
+    }
+
+    /// Let whoever holds the token hashing to `token_hash` manage the secret.
+    pub fn with_management_token_hash(mut self, token_hash: String) -> Self {
+        self.management_token_hash = Some(token_hash);
+        self
+    }
+
+    /// Record what becomes of the secret on the receipt with id `receipt_id`.
+    pub fn with_receipt_id(mut self, receipt_id: String) -> Self {
+        self.receipt_id = Some(receipt_id);
+        self
+    }
+
+    /// Record that the [`IdempotencyRecord`] stored under `key` replays the
+    /// secret's creation.
+    pub fn with_idempotency_key(mut self, key: String) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
+    /// Count one wrong PIN, returning how many attempts are left out of
+    /// `max_attempts`. Zero means the secret must now be burned.
+    pub fn record_failed_pin(&mut self, max_attempts: u32) -> u32 {
+        self.failed_pin_attempts = self.failed_pin_attempts.saturating_add(1);
+        max_attempts.saturating_sub(self.failed_pin_attempts)
+    }
+
     /// Mark the secret as having been read at the provided instant.
     pub fn mark_read(&mut self, when: OffsetDateTime) {
         self.read_at = Some(when);
     }
+
+    /// Record one read at `when`, using up a view.
+    ///
+    /// Returns `true` when that was the last view and the secret must now be
+    /// deleted.
+    pub fn consume_view(&mut self, when: OffsetDateTime) -> bool {
+        self.mark_read(when);
+        self.remaining_views = self.remaining_views.saturating_sub(1);
+        self.remaining_views == 0
+    }
+}
+
+/// Why a secret is no longer available.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TombstoneReason {
+    /// Its last view was read.
+    Read,
+    /// Its TTL ran out before it was read.
+    Expired,
+    /// The sender revoked it.
+    Revoked,
+    /// Too many wrong PINs were entered.
+    PinLockout,
+}
+
+/// A short-lived marker left where a secret used to be, so that late readers
+/// can be told it is gone rather than that it never existed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Tombstone {
+    pub reason: TombstoneReason,
+    /// When the last view was read, for [`TombstoneReason::Read`].
+    pub read_at: Option<OffsetDateTime>,
+    /// When the tombstone itself may be forgotten.
+    pub expires_at: OffsetDateTime,
+}
+
+impl Tombstone {
+    pub fn new(reason: TombstoneReason, expires_at: OffsetDateTime) -> Self {
+        Self {
+            reason,
+            read_at: None,
+            expires_at,
+        }
+    }
+
+    /// A tombstone for `secret`, which has just had its last view read.
+    pub fn read(secret: &Secret, expires_at: OffsetDateTime) -> Self {
+        Self {
+            read_at: secret.read_at,
+            ..Self::new(TombstoneReason::Read, expires_at)
+        }
+    }
+
+    pub fn is_expired_at(&self, now: OffsetDateTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// What a [`Receipt`] says became of its secret.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptStatus {
+    /// Nothing has happened to the secret yet.
+    Pending,
+    /// The secret was read.
+    Read,
+    /// The secret's TTL ran out before it was read.
+    Expired,
+    /// The sender revoked the secret.
+    Revoked,
+    /// Too many wrong PINs were entered.
+    PinLockout,
+}
+
+/// Lets the sender find out what became of a secret without being able to
+/// read it. Receipts have their own random id and outlive their secret.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Receipt {
+    pub id: String,
+    pub status: ReceiptStatus,
+    /// When the secret was first read.
+    pub read_at: Option<OffsetDateTime>,
+    /// Coarse description of the first reader's client, such as `Firefox`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_hint: Option<String>,
+    /// When the secret expires if nobody reads it.
+    pub secret_expires_at: OffsetDateTime,
+    /// When the receipt itself may be forgotten.
+    pub expires_at: OffsetDateTime,
+}
+
+impl Receipt {
+    /// A pending receipt with id `id` for `secret`.
+    pub fn new(id: String, secret: &Secret, expires_at: OffsetDateTime) -> Self {
+        Self {
+            id,
+            status: ReceiptStatus::Pending,
+            read_at: None,
+            client_hint: None,
+            secret_expires_at: secret.expires_at(),
+            expires_at,
+        }
+    }
+
+    /// The status as of `now`: a pending receipt whose secret has run out
+    /// reports it as expired, since stores delete expired secrets silently.
+    pub fn status_at(&self, now: OffsetDateTime) -> ReceiptStatus {
+        if self.status == ReceiptStatus::Pending && now >= self.secret_expires_at {
+            ReceiptStatus::Expired
+        } else {
+            self.status
+        }
+    }
+
+    /// Record the first thing that happened to the secret. Later events,
+    /// such as further views of a multi-view secret, leave the receipt as it
+    /// is; returns whether anything changed.
+    pub fn settle(
+        &mut self,
+        status: ReceiptStatus,
+        read_at: Option<OffsetDateTime>,
+        client_hint: Option<String>,
+    ) -> bool {
+        if self.status != ReceiptStatus::Pending {
+            return false;
+        }
+
+        self.status = status;
+        self.read_at = read_at;
+        self.client_hint = client_hint;
+        true
+    }
+
+    pub fn is_expired_at(&self, now: OffsetDateTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// What a create request sent with an `Idempotency-Key` produced, so that
+/// retries of it get the same secret back.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IdempotencyRecord {
+    /// Fingerprint of the request, to tell a retry from a different request
+    /// reusing the key.
+    pub request_hash: String,
+    pub secret_id: String,
+    /// The management token, sealed with the idempotency key so that only a
+    /// retry presenting that key can be given it again; see
+    /// [`crate::token::seal_token`].
+    pub sealed_management_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt_id: Option<String>,
+    pub secret_expires_at: OffsetDateTime,
+    /// When the key may be reused for a new secret.
+    pub expires_at: OffsetDateTime,
+}
+
+impl IdempotencyRecord {
+    pub fn is_expired_at(&self, now: OffsetDateTime) -> bool {
+        now >= self.expires_at
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +455,49 @@ mod tests {
             secret.read_at.is_none(),
             "new secrets should not be marked as read"
         );
+        assert_eq!(
+            secret.remaining_views, 1,
+            "secrets are single-view by default"
+        );
+    }
+
+    #[test]
+    fn expiry_tombstone_follows_the_secrets_expiry() {
+        let secret = Secret::new("c".into(), "i".into(), 60);
+        assert_eq!(secret.expiry_tombstone(), None);
+
+        let mut secret = secret.with_tombstone_secs(300);
+        let tombstone = secret.expiry_tombstone().expect("tombstone_secs is set");
+        assert_eq!(tombstone.reason, TombstoneReason::Expired);
+        assert_eq!(
+            tombstone.expires_at,
+            secret.expires_at() + Duration::seconds(300)
+        );
+
+        secret.ttl_secs = 120;
+        assert_eq!(
+            secret
+                .expiry_tombstone()
+                .map(|tombstone| tombstone.expires_at),
+            Some(secret.expires_at() + Duration::seconds(300))
+        );
+    }
+
+    #[test]
+    fn read_tombstone_is_left_only_by_the_last_view() {
+        let when = OffsetDateTime::UNIX_EPOCH + Duration::seconds(42);
+        let mut secret = Secret::new("c".into(), "i".into(), 60)
+            .with_max_views(2)
+            .with_tombstone_secs(300);
+
+        secret.consume_view(when);
+        assert_eq!(secret.read_tombstone(), None);
+
+        secret.consume_view(when);
+        let tombstone = secret.read_tombstone().expect("the last view was read");
+        assert_eq!(tombstone.reason, TombstoneReason::Read);
+        assert_eq!(tombstone.read_at, Some(when));
+        assert_eq!(tombstone.expires_at, when + Duration::seconds(300));
     }
 
     #[test]
@@ -117,4 +536,106 @@ mod tests {
         secret.mark_read(when);
         assert_eq!(secret.read_at, Some(when));
     }
+
+    #[test]
+    fn consume_view_counts_down_to_deletion() {
+        let mut secret = Secret::new("c".into(), "i".into(), 10).with_max_views(2);
+        let when = OffsetDateTime::UNIX_EPOCH;
+
+        assert!(!secret.consume_view(when));
+        assert_eq!(secret.remaining_views, 1);
+        assert_eq!(secret.read_at, Some(when));
+        assert!(secret.consume_view(when));
+        assert_eq!(secret.remaining_views, 0);
+    }
+
+    #[test]
+    fn records_without_a_view_count_are_single_view() {
+        let json = r#"{"id":"abc","ciphertext":"c","iv":"i","created_at":[2026,1,0,0,0,0,0,0,0],"ttl_secs":10,"read_at":null}"#;
+        let secret: Secret = serde_json::from_str(json).expect("legacy record should decode");
+        assert_eq!(secret.remaining_views, 1);
+    }
+
+    #[test]
+    fn record_failed_pin_counts_down_attempts() {
+        let mut secret = Secret::new("c".into(), "i".into(), 10).with_pin_hash("hash".into());
+
+        assert_eq!(secret.record_failed_pin(3), 2);
+        assert_eq!(secret.record_failed_pin(3), 1);
+        assert_eq!(secret.record_failed_pin(3), 0);
+        assert_eq!(secret.failed_pin_attempts, 3);
+    }
+
+    #[test]
+    fn receipt_keeps_the_first_event_and_expires_with_its_secret() {
+        let mut secret = Secret::new("c".into(), "i".into(), 30);
+        secret.created_at = OffsetDateTime::UNIX_EPOCH;
+        let mut receipt = Receipt::new(
+            "receipt".into(),
+            &secret,
+            secret.expires_at() + Duration::hours(1),
+        );
+
+        let before_expiry = secret.expires_at() - Duration::seconds(1);
+        assert_eq!(receipt.status_at(before_expiry), ReceiptStatus::Pending);
+        assert_eq!(
+            receipt.status_at(secret.expires_at()),
+            ReceiptStatus::Expired
+        );
+
+        assert!(receipt.settle(
+            ReceiptStatus::Read,
+            Some(before_expiry),
+            Some("Firefox".into())
+        ));
+        assert!(!receipt.settle(ReceiptStatus::Revoked, None, None));
+        assert_eq!(receipt.status_at(secret.expires_at()), ReceiptStatus::Read);
+        assert_eq!(receipt.read_at, Some(before_expiry));
+        assert_eq!(receipt.client_hint.as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn embargo_delays_availability_and_optionally_expiry() {
+        let created_at = OffsetDateTime::UNIX_EPOCH;
+        let not_before = created_at + Duration::hours(1);
+        let mut secret = Secret::new("c".into(), "i".into(), 60)
+            .with_not_before(not_before, TtlStart::CreatedAt);
+        secret.created_at = created_at;
+
+        assert!(!secret.is_available_at(not_before - Duration::seconds(1)));
+        assert!(secret.is_available_at(not_before));
+        assert_eq!(secret.expires_at(), created_at + Duration::seconds(60));
+
+        secret.ttl_start = TtlStart::NotBefore;
+        assert_eq!(secret.expires_at(), not_before + Duration::seconds(60));
+        assert!(Secret::new("c".into(), "i".into(), 60).is_available_at(created_at));
+    }
+
+    #[test]
+    fn claimed_copy_lives_for_the_grace_window_only() {
+        let when = OffsetDateTime::UNIX_EPOCH + Duration::hours(1);
+        let mut secret = Secret::new("c".into(), "i".into(), 3600)
+            .with_pin_hash("hash".into())
+            .with_receipt_id("receipt".into());
+        assert!(secret.claimed_copy("claim".into()).is_none());
+
+        secret = secret.with_grace_secs(30);
+        secret.mark_read(when);
+        let copy = secret.claimed_copy("claim".into()).unwrap();
+        assert_eq!(copy.id, "claim");
+        assert_eq!((copy.ciphertext.as_str(), copy.iv.as_str()), ("c", "i"));
+        assert_eq!(copy.expires_at(), when + Duration::seconds(30));
+        assert!(copy.pin_hash.is_none() && copy.receipt_id.is_none());
+        assert!(copy.grace_secs.is_none());
+        assert!(copy.claimed && copy.metadata().claimed);
+        assert!(!secret.claimed);
+
+        // The copy never outlives the secret it was taken from.
+        secret.mark_read(secret.expires_at() - Duration::seconds(10));
+        let copy = secret.claimed_copy("claim".into()).unwrap();
+        assert_eq!(copy.expires_at(), secret.expires_at());
+
+        secret.mark_read(secret.expires_at() - Duration::milliseconds(500));
+        assert!(secret.claimed_copy("claim".into()).is_none());
+    }
 }
@@ -0,0 +1,52 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+
+/// Shortest PIN accepted at creation time.
+pub const MIN_PIN_LEN: usize = 4;
+/// Longest PIN accepted at creation time.
+pub const MAX_PIN_LEN: usize = 64;
+
+/// Hash `pin` into an Argon2id PHC string with a fresh random salt.
+///
+/// PINs are short, so only this deliberately slow hash is ever stored; the
+/// attempt limit enforced by the stores does the rest.
+pub fn hash_pin(pin: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Check `pin` against a PHC string produced by [`hash_pin`].
+///
+/// A malformed hash never verifies.
+pub fn verify_pin(pin: &str, pin_hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(pin_hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(pin.as_bytes(), &parsed)
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_verify_only_the_original_pin() {
+        let hash = hash_pin("4821").expect("hashing should succeed");
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!hash.contains("4821"));
+        assert!(verify_pin("4821", &hash));
+        assert!(!verify_pin("4822", &hash));
+    }
+
+    #[test]
+    fn malformed_hash_never_verifies() {
+        assert!(!verify_pin("4821", "not a phc string"));
+    }
+}
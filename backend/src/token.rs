@@ -0,0 +1,126 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Random bytes in a management token.
+const TOKEN_BYTES: usize = 32;
+
+/// Bytes of the random nonce a sealed token starts with.
+const NONCE_BYTES: usize = 12;
+
+/// Keeps the sealing key apart from [`hash_token`] of the same passphrase,
+/// which may be stored next to the sealed token.
+const SEAL_KEY_CONTEXT: &[u8] = b"cendre sealed token v1\n";
+
+/// Generate a fresh management token, URL-safe base64 encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hash a management token for storage.
+///
+/// Tokens carry 256 bits of randomness, so a plain SHA-256 is enough; unlike
+/// PINs they cannot be brute-forced from a leaked hash.
+pub fn hash_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+/// The id under which the claimed copy of secret `secret_id` is kept for
+/// whoever holds `claim_token`.
+///
+/// Deriving it from the token means nothing needs to link the copy back to
+/// the secret, and only the claim holder can find it.
+pub fn claim_id(secret_id: &str, claim_token: &str) -> String {
+    hash_token(&format!("{secret_id}:{claim_token}"))
+}
+
+/// Encrypt `token` under a key derived from `passphrase`, bound to `context`,
+/// so that it can be stored and handed out again only to whoever presents
+/// the passphrase.
+pub fn seal_token(token: &str, passphrase: &str, context: &str) -> String {
+    let mut nonce = [0u8; NONCE_BYTES];
+    OsRng.fill_bytes(&mut nonce);
+
+    let payload = Payload {
+        msg: token.as_bytes(),
+        aad: context.as_bytes(),
+    };
+    let ciphertext = sealing_cipher(passphrase)
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .expect("sealing a token cannot fail");
+
+    URL_SAFE_NO_PAD.encode([nonce.as_slice(), &ciphertext].concat())
+}
+
+/// Recover a token sealed by [`seal_token`], or `None` if `passphrase` or
+/// `context` differ from the ones it was sealed with.
+pub fn open_token(sealed: &str, passphrase: &str, context: &str) -> Option<String> {
+    let sealed = URL_SAFE_NO_PAD.decode(sealed).ok()?;
+    if sealed.len() < NONCE_BYTES {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_BYTES);
+
+    let payload = Payload {
+        msg: ciphertext,
+        aad: context.as_bytes(),
+    };
+    let token = sealing_cipher(passphrase)
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .ok()?;
+    String::from_utf8(token).ok()
+}
+
+fn sealing_cipher(passphrase: &str) -> ChaCha20Poly1305 {
+    let key = Sha256::new()
+        .chain_update(SEAL_KEY_CONTEXT)
+        .chain_update(passphrase.as_bytes())
+        .finalize();
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+/// Check `token` against a hash produced by [`hash_token`] in constant time.
+pub fn verify_token(token: &str, token_hash: &str) -> bool {
+    hash_token(token)
+        .as_bytes()
+        .ct_eq(token_hash.as_bytes())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_and_verify_against_their_hash() {
+        let token = generate_token();
+        assert_ne!(token, generate_token());
+
+        let hash = hash_token(&token);
+        assert_ne!(hash, token);
+        assert!(verify_token(&token, &hash));
+        assert!(!verify_token(&generate_token(), &hash));
+    }
+
+    #[test]
+    fn sealed_tokens_open_only_with_their_passphrase_and_context() {
+        let token = generate_token();
+        let sealed = seal_token(&token, "passphrase", "context");
+        assert!(!sealed.contains(&token));
+        assert_ne!(sealed, seal_token(&token, "passphrase", "context"));
+
+        assert_eq!(
+            open_token(&sealed, "passphrase", "context").as_deref(),
+            Some(token.as_str())
+        );
+        assert_eq!(open_token(&sealed, "other", "context"), None);
+        assert_eq!(open_token(&sealed, "passphrase", "other"), None);
+        assert_eq!(open_token("not sealed", "passphrase", "context"), None);
+    }
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+
+/// A named TTL offered to clients, such as `1 hour` for 3600 seconds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TtlPreset {
+    pub name: String,
+    pub ttl_secs: u32,
+}
+
+impl TtlPreset {
+    pub fn new(name: &str, ttl_secs: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            ttl_secs,
+        }
+    }
+}
+
+/// Which TTLs the server accepts, and which it suggests.
+///
+/// TTLs up to `max_secs` are allowed on every store. Above that, up to
+/// `long_max_secs`, lies the long tier, which is only allowed on stores that
+/// report themselves durable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TtlPolicy {
+    pub min_secs: u32,
+    pub max_secs: u32,
+    /// Upper bound of the long tier; equal to `max_secs` to turn it off.
+    pub long_max_secs: u32,
+    /// Used when a client sends neither `ttl_secs` nor `expires_at`.
+    pub default_secs: u32,
+    pub presets: Vec<TtlPreset>,
+}
+
+impl Default for TtlPolicy {
+    fn default() -> Self {
+        Self {
+            min_secs: 1,
+            max_secs: 24 * 60 * 60,
+            long_max_secs: 30 * 24 * 60 * 60,
+            default_secs: 60 * 60,
+            presets: vec![
+                TtlPreset::new("5 minutes", 5 * 60),
+                TtlPreset::new("1 hour", 60 * 60),
+                TtlPreset::new("24 hours", 24 * 60 * 60),
+                TtlPreset::new("7 days", 7 * 24 * 60 * 60),
+                TtlPreset::new("30 days", 30 * 24 * 60 * 60),
+            ],
+        }
+    }
+}
+
+impl TtlPolicy {
+    /// Whether a secret may live for `ttl_secs`, on a durable store if
+    /// `durable` is set.
+    pub fn allows(&self, ttl_secs: u32, durable: bool) -> bool {
+        let max_secs = if durable {
+            self.long_max_secs
+        } else {
+            self.max_secs
+        };
+        (self.min_secs..=max_secs).contains(&ttl_secs)
+    }
+
+    /// Whether `ttl_secs` falls in the long tier.
+    pub fn is_long(&self, ttl_secs: u32) -> bool {
+        ttl_secs > self.max_secs
+    }
+
+    /// Check that the bounds make sense and that the default and every
+    /// preset fall within them. The default must work on any store.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_secs == 0 || self.min_secs > self.max_secs {
+            return Err(format!(
+                "TTL bounds {}..={} are empty or start at zero",
+                self.min_secs, self.max_secs
+            ));
+        }
+        if self.long_max_secs < self.max_secs {
+            return Err(format!(
+                "long tier maximum {} is below the maximum TTL {}",
+                self.long_max_secs, self.max_secs
+            ));
+        }
+        if !self.allows(self.default_secs, false) {
+            return Err(format!(
+                "default TTL {} is outside {}..={}",
+                self.default_secs, self.min_secs, self.max_secs
+            ));
+        }
+        if let Some(preset) = self.presets.iter().find(|p| !self.allows(p.ttl_secs, true)) {
+            return Err(format!(
+                "preset {:?} ({} seconds) is outside {}..={}",
+                preset.name, preset.ttl_secs, self.min_secs, self.long_max_secs
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse presets written as comma-separated `name=seconds` pairs, for
+/// example `5 minutes=300,1 hour=3600`.
+pub fn parse_presets(value: &str) -> Result<Vec<TtlPreset>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, secs) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| format!("preset {entry:?} is not name=seconds"))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(format!("preset {entry:?} has no name"));
+            }
+            let ttl_secs = secs
+                .trim()
+                .parse()
+                .map_err(|_| format!("preset {entry:?} has an invalid number of seconds"))?;
+            Ok(TtlPreset::new(name, ttl_secs))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_is_valid() {
+        let policy = TtlPolicy::default();
+        assert_eq!(policy.validate(), Ok(()));
+        assert!(policy.allows(1, false) && policy.allows(86_400, false));
+        assert!(!policy.allows(0, false) && !policy.allows(86_401, false));
+    }
+
+    #[test]
+    fn long_tier_needs_a_durable_store() {
+        let policy = TtlPolicy::default();
+        let week = 7 * 24 * 60 * 60;
+
+        assert!(policy.is_long(week) && !policy.is_long(86_400));
+        assert!(!policy.allows(week, false));
+        assert!(policy.allows(week, true));
+        assert!(!policy.allows(policy.long_max_secs + 1, true));
+    }
+
+    #[test]
+    fn rejects_inconsistent_policies() {
+        let inverted = TtlPolicy {
+            min_secs: 600,
+            max_secs: 60,
+            ..TtlPolicy::default()
+        };
+        assert!(inverted.validate().is_err());
+
+        let stray_default = TtlPolicy {
+            default_secs: 100_000,
+            ..TtlPolicy::default()
+        };
+        assert!(stray_default.validate().is_err());
+
+        let stray_preset = TtlPolicy {
+            long_max_secs: 7 * 24 * 60 * 60,
+            ..TtlPolicy::default()
+        };
+        assert!(stray_preset.validate().unwrap_err().contains("30 days"));
+
+        let short_long_tier = TtlPolicy {
+            long_max_secs: 3600,
+            ..TtlPolicy::default()
+        };
+        assert!(short_long_tier.validate().is_err());
+    }
+
+    #[test]
+    fn parses_presets() {
+        assert_eq!(
+            parse_presets("10 minutes=600, 1 day = 86400,"),
+            Ok(vec![
+                TtlPreset::new("10 minutes", 600),
+                TtlPreset::new("1 day", 86_400),
+            ])
+        );
+        assert!(parse_presets("forever").is_err());
+        assert!(parse_presets("=60").is_err());
+        assert!(parse_presets("soon=later").is_err());
+    }
+}
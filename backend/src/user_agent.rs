@@ -0,0 +1,62 @@
+/// Reduce a `User-Agent` header to a coarse client family such as `Firefox`.
+///
+/// Only the family is ever kept, never versions or platform details, so a
+/// receipt tells the sender roughly what opened the secret without
+/// fingerprinting the recipient. Order matters: most browsers also claim to
+/// be the ones they descend from.
+pub fn family(user_agent: &str) -> &'static str {
+    const FAMILIES: &[(&str, &str)] = &[
+        ("Edg/", "Edge"),
+        ("EdgA/", "Edge"),
+        ("OPR/", "Opera"),
+        ("SamsungBrowser/", "Samsung Internet"),
+        ("Firefox/", "Firefox"),
+        ("FxiOS/", "Firefox"),
+        ("CriOS/", "Chrome"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+        ("Wget/", "Wget"),
+    ];
+
+    FAMILIES
+        .iter()
+        .find(|(marker, _)| user_agent.contains(marker))
+        .map_or("Other", |(_, family)| family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_common_clients() {
+        let cases = [
+            (
+                "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0",
+                "Firefox",
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+                 Chrome/129.0.0.0 Safari/537.36",
+                "Chrome",
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+                 Chrome/129.0.0.0 Safari/537.36 Edg/129.0.0.0",
+                "Edge",
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_6 like Mac OS X) AppleWebKit/605.1.15 \
+                 (KHTML, like Gecko) Version/17.6 Mobile/15E148 Safari/604.1",
+                "Safari",
+            ),
+            ("curl/8.9.1", "curl"),
+            ("Slackbot-LinkExpanding 1.0", "Other"),
+        ];
+
+        for (user_agent, expected) in cases {
+            assert_eq!(family(user_agent), expected, "for {user_agent:?}");
+        }
+    }
+}
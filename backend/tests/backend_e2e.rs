@@ -4,6 +4,7 @@ use axum::{
 };
 use http_body_util::BodyExt;
 use serde_json::Value;
+use tokio::task::JoinSet;
 use tower::ServiceExt; // for `oneshot`
 
 use cendre_backend::app_router_with_in_memory_store;
@@ -54,8 +55,8 @@ async fn create_then_read_secret_end_to_end() {
         .clone()
         .oneshot(
             Request::builder()
-                .method("GET")
-                .uri(format!("/api/secret/{id}"))
+                .method("POST")
+                .uri(format!("/api/secret/{id}/reveal"))
                 .body(Body::empty())
                 .expect("failed to build request"),
         )
@@ -82,17 +83,464 @@ async fn create_then_read_secret_end_to_end() {
         Some("iv-value")
     );
 
-    // Second read: the secret should have been deleted and we should see a 404.
+    // Second read: the secret has been deleted, and its tombstone says so.
     let second_read = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/secret/{id}/reveal"))
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+
+    assert_eq!(second_read.status(), StatusCode::GONE);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_reads_release_the_secret_exactly_once() {
+    let app = app_router_with_in_memory_store();
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body collection should succeed")
+        .to_bytes();
+    let json: Value =
+        serde_json::from_slice(&body_bytes).expect("response body should be valid JSON");
+    let id = json
+        .get("id")
+        .and_then(|v| v.as_str())
+        .expect("response should contain an id")
+        .to_string();
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..32 {
+        let app = app.clone();
+        let uri = format!("/api/secret/{id}/reveal");
+        tasks.spawn(async move {
+            app.oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request to router should succeed")
+            .status()
+        });
+    }
+
+    let mut ok = 0;
+    while let Some(status) = tasks.join_next().await {
+        let status = status.expect("reader task should not panic");
+        if status == StatusCode::OK {
+            ok += 1;
+        } else {
+            assert_eq!(status, StatusCode::GONE);
+        }
+    }
+
+    assert_eq!(ok, 1, "exactly one concurrent reader should get the secret");
+}
+
+#[tokio::test]
+async fn store_health_reports_ok_for_in_memory_store() {
+    let app = app_router_with_in_memory_store();
+
+    let response = app
         .oneshot(
             Request::builder()
                 .method("GET")
-                .uri(format!("/api/secret/{id}"))
+                .uri("/health/store")
                 .body(Body::empty())
                 .expect("failed to build request"),
         )
         .await
         .expect("request to router should succeed");
 
-    assert_eq!(second_read.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body collection should succeed")
+        .to_bytes();
+    let json: Value =
+        serde_json::from_slice(&body_bytes).expect("response body should be valid JSON");
+
+    assert_eq!(json.get("status").and_then(|v| v.as_str()), Some("ok"));
+    assert!(
+        json.get("pool").is_none(),
+        "pool statistics require the health token"
+    );
+}
+
+#[tokio::test]
+async fn multi_view_secret_reports_remaining_views() {
+    let app = app_router_with_in_memory_store();
+
+    let create = |max_views: u32| {
+        let payload = serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 60u32,
+            "max_views": max_views,
+        });
+        Request::builder()
+            .method("POST")
+            .uri("/api/secrets")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("failed to build request")
+    };
+
+    for invalid in [0, 101] {
+        let response = app.clone().oneshot(create(invalid)).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::BAD_REQUEST,
+            "max_views = {invalid} should be rejected"
+        );
+    }
+
+    let response = app.clone().oneshot(create(2)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let id = json["id"].as_str().expect("response should contain an id");
+
+    let read = || {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/secret/{id}/reveal"))
+            .body(Body::empty())
+            .expect("failed to build request")
+    };
+
+    for expected_remaining in [1, 0] {
+        let response = app.clone().oneshot(read()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["ciphertext"], "ciphertext-value");
+        assert_eq!(json["remaining_views"], expected_remaining);
+    }
+
+    let response = app.clone().oneshot(read()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn pin_protected_secret_burns_after_too_many_wrong_pins() {
+    let app = app_router_with_in_memory_store();
+
+    let create = |pin: &str| {
+        let payload = serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 60u32,
+            "pin": pin,
+        });
+        Request::builder()
+            .method("POST")
+            .uri("/api/secrets")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("failed to build request")
+    };
+    let read = |id: &str, pin: Option<&str>| {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/secret/{id}/reveal"));
+        if let Some(pin) = pin {
+            request = request.header("x-secret-pin", pin);
+        }
+        request
+            .body(Body::empty())
+            .expect("failed to build request")
+    };
+    let json_of = |response: axum::response::Response| async move {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    let response = app.clone().oneshot(create("123")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The right PIN releases the secret, even after a wrong guess.
+    let response = app.clone().oneshot(create("4821")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let id = json_of(response).await["id"].as_str().unwrap().to_string();
+
+    let response = app.clone().oneshot(read(&id, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = app.clone().oneshot(read(&id, Some("0000"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(json_of(response).await["attempts_left"], 4);
+
+    let response = app.clone().oneshot(read(&id, Some("4821"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_of(response).await["ciphertext"], "ciphertext-value");
+
+    // Five wrong guesses burn it for good.
+    let response = app.clone().oneshot(create("4821")).await.unwrap();
+    let id = json_of(response).await["id"].as_str().unwrap().to_string();
+
+    for expected_left in (0..5).rev() {
+        let response = app.clone().oneshot(read(&id, Some("0000"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(json_of(response).await["attempts_left"], expected_left);
+    }
+
+    let response = app.clone().oneshot(read(&id, Some("4821"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn sender_can_revoke_secret_with_management_token() {
+    let app = app_router_with_in_memory_store();
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+        "max_views": 3u32,
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let id = json["id"].as_str().unwrap().to_string();
+    let token = json["management_token"]
+        .as_str()
+        .expect("response should contain a management token")
+        .to_string();
+
+    let revoke = |authorization: Option<String>| {
+        let mut request = Request::builder()
+            .method("DELETE")
+            .uri(format!("/api/secret/{id}"));
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        request
+            .body(Body::empty())
+            .expect("failed to build request")
+    };
+
+    let response = app.clone().oneshot(revoke(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = app
+        .clone()
+        .oneshot(revoke(Some("Bearer not-the-token".into())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .clone()
+        .oneshot(revoke(Some(format!("Bearer {token}"))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Views that were left are gone too.
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/secret/{id}/reveal"))
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+
+    let response = app
+        .oneshot(revoke(Some(format!("Bearer {token}"))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn metadata_describes_secret_without_consuming_it() {
+    let app = app_router_with_in_memory_store();
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let id = json["id"].as_str().unwrap().to_string();
+
+    let request = |method: &str, uri: String| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .expect("failed to build request")
+    };
+
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(request("GET", format!("/api/secret/{id}/meta")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let meta: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(meta["id"], id.as_str());
+        assert_eq!(meta["ciphertext_size"], "ciphertext-value".len());
+        assert_eq!(meta["remaining_views"], 1);
+        assert_eq!(meta["pin_required"], false);
+        assert!(meta["created_at"].is_string());
+        assert!(meta["expires_at"].is_string());
+        assert!(meta.get("ciphertext").is_none() && meta.get("iv").is_none());
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request("HEAD", format!("/api/secret/{id}/meta")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty());
+
+    // The secret is still there to be read, after which it has no metadata.
+    let response = app
+        .clone()
+        .oneshot(request("POST", format!("/api/secret/{id}/reveal")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for method in ["GET", "HEAD"] {
+        let response = app
+            .clone()
+            .oneshot(request(method, format!("/api/secret/{id}/meta")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::GONE);
+    }
+}
+
+#[tokio::test]
+async fn consumed_secret_answers_gone_with_read_time() {
+    let app = app_router_with_in_memory_store();
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let id = json["id"].as_str().unwrap().to_string();
+
+    let reveal = || {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/secret/{id}/reveal"))
+            .body(Body::empty())
+            .expect("failed to build request")
+    };
+    let response = app.clone().oneshot(reveal()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for request in [
+        reveal(),
+        Request::builder()
+            .uri(format!("/api/secret/{id}/meta"))
+            .body(Body::empty())
+            .expect("failed to build request"),
+    ] {
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GONE);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["reason"], "read");
+        assert!(json["read_at"].is_string());
+        assert!(json.get("ciphertext").is_none());
+    }
+
+    // Ids that never existed are still plain 404s.
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secret/does-not-exist/reveal")
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
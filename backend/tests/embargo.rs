@@ -0,0 +1,161 @@
+use std::time::Duration as StdDuration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::app_router_with_in_memory_store;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+use tower::ServiceExt; // for `oneshot`
+
+async fn send(app: &Router, method: &str, uri: String, body: Option<Value>) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if body.is_some() {
+        request = request.header("content-type", "application/json");
+    }
+    let request = request
+        .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+        .expect("failed to build request");
+
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn rfc3339(at: OffsetDateTime) -> String {
+    at.format(&Rfc3339).unwrap()
+}
+
+fn parse(value: &Value) -> OffsetDateTime {
+    OffsetDateTime::parse(
+        value.as_str().expect("timestamp should be a string"),
+        &Rfc3339,
+    )
+    .expect("timestamp should be RFC 3339")
+}
+
+#[tokio::test]
+async fn embargoed_secret_is_locked_until_not_before() {
+    let app = app_router_with_in_memory_store();
+    let not_before = OffsetDateTime::now_utc() + Duration::hours(1);
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+        "not_before": rfc3339(not_before),
+        "ttl_start": "not_before",
+    });
+    let (status, created) = send(&app, "POST", "/api/secrets".into(), Some(payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created["id"].as_str().unwrap();
+
+    for (method, uri) in [
+        ("GET", format!("/api/secret/{id}")),
+        ("POST", format!("/api/secret/{id}/reveal")),
+    ] {
+        let (status, body) = send(&app, method, uri, None).await;
+        assert_eq!(status, StatusCode::LOCKED, "{method} should be locked");
+        assert_eq!(
+            parse(&body["unlock_at"]).unix_timestamp(),
+            not_before.unix_timestamp()
+        );
+        assert!(body.get("ciphertext").is_none());
+    }
+
+    // Metadata is still available, and the TTL counts from not_before.
+    let (status, meta) = send(&app, "GET", format!("/api/secret/{id}/meta"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        parse(&meta["not_before"]).unix_timestamp(),
+        not_before.unix_timestamp()
+    );
+    assert_eq!(
+        parse(&meta["expires_at"]).unix_timestamp(),
+        (not_before + Duration::seconds(60)).unix_timestamp()
+    );
+}
+
+#[tokio::test]
+async fn embargoed_secret_unlocks_when_not_before_arrives() {
+    let app = app_router_with_in_memory_store();
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+        "not_before": rfc3339(OffsetDateTime::now_utc() + Duration::seconds(1)),
+    });
+    let (status, created) = send(&app, "POST", "/api/secrets".into(), Some(payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created["id"].as_str().unwrap();
+
+    let (status, _) = send(&app, "POST", format!("/api/secret/{id}/reveal"), None).await;
+    assert_eq!(status, StatusCode::LOCKED);
+
+    tokio::time::sleep(StdDuration::from_millis(1100)).await;
+    let (status, body) = send(&app, "POST", format!("/api/secret/{id}/reveal"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ciphertext"], "ciphertext-value");
+}
+
+#[tokio::test]
+async fn embargo_must_end_before_the_secret_expires() {
+    let app = app_router_with_in_memory_store();
+    let now = OffsetDateTime::now_utc();
+
+    // Counting from creation, the TTL runs out an hour early; counting from
+    // not_before, the embargo itself is too long.
+    for (not_before, ttl_start) in [
+        (now + Duration::hours(1), "created_at"),
+        (now + Duration::days(31), "not_before"),
+    ] {
+        let payload = serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 60u32,
+            "not_before": rfc3339(not_before),
+            "ttl_start": ttl_start,
+        });
+        let (status, _) = send(&app, "POST", "/api/secrets".into(), Some(payload)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn ttl_update_cannot_end_the_secret_before_its_embargo() {
+    let app = app_router_with_in_memory_store();
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 7200u32,
+        "not_before": rfc3339(OffsetDateTime::now_utc() + Duration::hours(1)),
+    });
+    let (status, created) = send(&app, "POST", "/api/secrets".into(), Some(payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created["id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+
+    for (ttl_secs, expected) in [(600u32, StatusCode::BAD_REQUEST), (10_800, StatusCode::OK)] {
+        let request = Request::builder()
+            .method("PATCH")
+            .uri(format!("/api/secret/{id}"))
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::from(
+                serde_json::json!({ "ttl_secs": ttl_secs }).to_string(),
+            ))
+            .expect("failed to build request");
+        let status = app.clone().oneshot(request).await.unwrap().status();
+        assert_eq!(status, expected, "for ttl_secs {ttl_secs}");
+    }
+}
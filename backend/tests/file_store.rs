@@ -0,0 +1,396 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::app_router_with_store;
+use cendre_backend::db::{FileSecretStore, SecretStore};
+use cendre_backend::models::{IdempotencyRecord, Secret, Tombstone, TombstoneReason};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::task::JoinSet;
+use tower::ServiceExt; // for `oneshot`
+
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("cendre-files-{}", uuid::Uuid::new_v4()));
+        Self { path }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Files in the secret directory, leaving out tombstones and receipts (which
+/// hold no secret material).
+fn entries(dir: &TempDir) -> Vec<PathBuf> {
+    std::fs::read_dir(&dir.path)
+        .expect("secret directory should be readable")
+        .map(|entry| entry.expect("directory entry should be readable").path())
+        .filter(|path| {
+            path.extension()
+                .is_none_or(|ext| ext != "tombstone" && ext != "receipt")
+        })
+        .collect()
+}
+
+async fn create_secret(app: &Router, ttl_secs: u32) -> String {
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": ttl_secs,
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body collection should succeed")
+        .to_bytes();
+    let json: Value =
+        serde_json::from_slice(&body_bytes).expect("response body should be valid JSON");
+
+    json.get("id")
+        .and_then(|v| v.as_str())
+        .expect("response should contain an id")
+        .to_string()
+}
+
+async fn read_secret(app: &Router, id: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/secret/{id}/reveal"))
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed")
+        .status()
+}
+
+#[tokio::test]
+async fn file_store_serves_secret_once_through_the_api() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+    let app = app_router_with_store(Arc::new(store));
+
+    let id = create_secret(&app, 60).await;
+
+    let files = entries(&dir);
+    assert_eq!(files.len(), 1, "one file should be written per secret");
+    let mode = std::fs::metadata(&files[0])
+        .expect("secret file should exist")
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600, "secret files must be private");
+
+    assert_eq!(read_secret(&app, &id).await, StatusCode::OK);
+    assert_eq!(read_secret(&app, &id).await, StatusCode::GONE);
+    assert!(entries(&dir).is_empty(), "read secrets must be unlinked");
+}
+
+#[tokio::test]
+async fn file_store_rejects_ids_that_escape_the_directory() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(dir.path.join("secrets"))
+        .expect("FileSecretStore should create its dir");
+    std::fs::write(dir.path.join("outside.secret"), b"{}").expect("fixture should be written");
+
+    let fetched = store
+        .get_and_delete_secret("../outside")
+        .await
+        .expect("lookup should succeed");
+    assert!(fetched.is_none());
+    assert!(dir.path.join("outside.secret").exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn file_store_releases_secret_to_one_of_many_concurrent_readers() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+    let app = app_router_with_store(Arc::new(store));
+
+    let id = create_secret(&app, 60).await;
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let app = app.clone();
+        let id = id.clone();
+        tasks.spawn(async move { read_secret(&app, &id).await });
+    }
+
+    let mut ok = 0;
+    while let Some(status) = tasks.join_next().await {
+        let status = status.expect("reader task should not panic");
+        if status == StatusCode::OK {
+            ok += 1;
+        } else {
+            assert_eq!(status, StatusCode::GONE);
+        }
+    }
+    assert_eq!(ok, 1, "exactly one concurrent reader should get the secret");
+}
+
+#[tokio::test]
+async fn file_store_sweeper_removes_expired_files() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+
+    store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+    let live = store
+        .store_secret("ciphertext".into(), "iv".into(), 3600)
+        .await
+        .expect("store_secret should succeed");
+
+    tokio::time::sleep(StdDuration::from_millis(1100)).await;
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(50));
+    tokio::time::sleep(StdDuration::from_millis(200)).await;
+
+    let files = entries(&dir);
+    assert_eq!(files.len(), 1, "only the live secret should remain");
+    assert!(
+        files[0].to_string_lossy().contains(&live.id),
+        "the live secret must not be swept"
+    );
+
+    drop(store);
+    tokio::time::timeout(StdDuration::from_secs(1), sweeper)
+        .await
+        .expect("sweeper should stop once the store is dropped")
+        .expect("sweeper task should not panic");
+}
+
+#[tokio::test]
+async fn file_store_sweeper_removes_expired_tombstones() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+
+    let now = time::OffsetDateTime::now_utc();
+    let stale = Tombstone::new(TombstoneReason::Expired, now + time::Duration::seconds(1));
+    let live = Tombstone::new(TombstoneReason::Revoked, now + time::Duration::hours(1));
+    store
+        .put_tombstone("stale", &stale)
+        .await
+        .expect("put_tombstone should succeed");
+    store
+        .put_tombstone("live", &live)
+        .await
+        .expect("put_tombstone should succeed");
+
+    tokio::time::sleep(StdDuration::from_millis(1100)).await;
+    assert!(store.get_tombstone("stale").await.unwrap().is_none());
+
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(50));
+    tokio::time::sleep(StdDuration::from_millis(200)).await;
+    sweeper.abort();
+
+    let names: Vec<_> = std::fs::read_dir(&dir.path)
+        .expect("secret directory should be readable")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, vec!["live.tombstone".to_string()]);
+    assert_eq!(store.get_tombstone("live").await.unwrap(), Some(live));
+}
+
+#[tokio::test]
+async fn file_store_sweeper_removes_abandoned_temp_files() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+
+    // A write interrupted before its rename leaves a temporary file behind.
+    let stale = dir.path.join(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let fresh = dir.path.join(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    for path in [&stale, &fresh] {
+        std::fs::write(path, b"{\"ciphertext\":\"in-flight\"}")
+            .expect("temp file should be written");
+    }
+    std::fs::File::options()
+        .write(true)
+        .open(&stale)
+        .and_then(|file| {
+            file.set_modified(std::time::SystemTime::now() - StdDuration::from_secs(120))
+        })
+        .expect("temp file should be backdated");
+
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(50));
+    tokio::time::sleep(StdDuration::from_millis(200)).await;
+    sweeper.abort();
+
+    assert!(!stale.exists(), "abandoned temp file should be removed");
+    assert!(fresh.exists(), "a write in progress must be left alone");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn file_store_hands_out_each_view_of_a_multi_view_secret_once() {
+    let dir = TempDir::new();
+    let store = Arc::new(FileSecretStore::new(&dir.path).expect("file store should open"));
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+                .map(|secret| secret.remaining_views)
+        });
+    }
+
+    let mut remaining = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        remaining.extend(result.expect("reader task should not panic"));
+    }
+    remaining.sort_unstable();
+    assert_eq!(
+        remaining,
+        vec![0, 1, 2],
+        "each of the three views should be handed out exactly once"
+    );
+}
+
+#[tokio::test]
+async fn file_store_burns_secret_after_too_many_wrong_pins() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("file store should open");
+
+    let created = store
+        .insert_secret(
+            Secret::new("ciphertext".into(), "iv".into(), 60).with_pin_hash("pin-hash".into()),
+        )
+        .await
+        .expect("insert_secret should succeed");
+
+    let peeked = store
+        .peek_secret(&created.id)
+        .await
+        .expect("peek_secret should succeed")
+        .expect("peek should find the secret");
+    assert_eq!(peeked.pin_hash.as_deref(), Some("pin-hash"));
+
+    for expected_left in [2, 1, 0] {
+        let left = store
+            .record_failed_pin(&created.id, 3)
+            .await
+            .expect("record_failed_pin should succeed");
+        assert_eq!(left, Some(expected_left));
+    }
+
+    assert!(
+        store
+            .peek_secret(&created.id)
+            .await
+            .expect("peek_secret should succeed")
+            .is_none(),
+        "secret should be burned after the last wrong pin"
+    );
+}
+
+#[tokio::test]
+async fn file_store_idempotency_record_is_kept_until_it_expires() {
+    let dir = TempDir::new();
+    let store = FileSecretStore::new(&dir.path).expect("FileSecretStore should create its dir");
+    let record = |secret_id: &str, expires_in: time::Duration| IdempotencyRecord {
+        request_hash: "request-hash".into(),
+        secret_id: secret_id.into(),
+        sealed_management_token: "sealed-management-token".into(),
+        receipt_id: None,
+        secret_expires_at: time::OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        expires_at: time::OffsetDateTime::now_utc() + expires_in,
+    };
+    let key = uuid::Uuid::new_v4().simple().to_string();
+
+    let first = record("first", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&key, &first)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+    // A live record is never replaced, only reported.
+    let existing = store
+        .put_idempotency_record(&key, &record("second", time::Duration::minutes(1)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(existing.as_ref(), Some(&first));
+    assert_eq!(
+        store
+            .get_idempotency_record(&key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        Some(first)
+    );
+
+    // An expired one may be.
+    let other_key = uuid::Uuid::new_v4().simple().to_string();
+    store
+        .put_idempotency_record(&other_key, &record("stale", time::Duration::seconds(-5)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(
+        store
+            .get_idempotency_record(&other_key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        None
+    );
+    let fresh = record("fresh", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&other_key, &fresh)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+
+    // Nothing but the two records is left behind.
+    let mut names: Vec<_> = std::fs::read_dir(&dir.path)
+        .expect("secret directory should be readable")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let mut expected = vec![
+        format!("{key}.idempotency"),
+        format!("{other_key}.idempotency"),
+    ];
+    expected.sort();
+    assert_eq!(names, expected);
+}
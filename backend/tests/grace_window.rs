@@ -0,0 +1,202 @@
+use std::time::Duration as StdDuration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::app_router_with_in_memory_store;
+use cendre_backend::token::claim_id;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tower::ServiceExt; // for `oneshot`
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn create(payload: Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/api/secrets")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request")
+}
+
+fn reveal(id: &str, claim_token: Option<&str>) -> Request<Body> {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/secret/{id}/reveal"));
+    if let Some(claim_token) = claim_token {
+        request = request.header("x-claim-token", claim_token);
+    }
+    request
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn claim_holder_can_refetch_within_the_grace_window() {
+    let app = app_router_with_in_memory_store();
+    let (status, created) = send(
+        &app,
+        create(serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 60u32,
+            "grace_secs": 1u32,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created["id"].as_str().unwrap();
+
+    let (status, first) = send(&app, reveal(id, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["remaining_views"], 0);
+    assert!(first["claim_expires_at"].is_string());
+    let claim_token = first["claim_token"]
+        .as_str()
+        .expect("grace-mode reads should return a claim token");
+
+    // Everyone else is told the secret has been read.
+    let (status, body) = send(&app, reveal(id, None)).await;
+    assert_eq!(status, StatusCode::GONE);
+    assert_eq!(body["reason"], "read");
+    let (status, _) = send(&app, reveal(id, Some("not-the-claim-token"))).await;
+    assert_eq!(status, StatusCode::GONE);
+
+    // The claim holder gets the same secret again, as often as needed.
+    for _ in 0..2 {
+        let (status, again) = send(&app, reveal(id, Some(claim_token))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["id"], id);
+        assert_eq!(again["ciphertext"], "ciphertext-value");
+        assert_eq!(again["iv"], "iv-value");
+    }
+
+    tokio::time::sleep(StdDuration::from_millis(1100)).await;
+    let (status, _) = send(&app, reveal(id, Some(claim_token))).await;
+    assert_eq!(status, StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn secrets_without_grace_hand_out_no_claim() {
+    let app = app_router_with_in_memory_store();
+    let (_, created) = send(
+        &app,
+        create(serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 60u32,
+        })),
+    )
+    .await;
+    let id = created["id"].as_str().unwrap();
+
+    let (status, body) = send(&app, reveal(id, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("claim_token").is_none());
+}
+
+async fn read_with_grace(app: &Router, ttl_secs: u32, grace_secs: u32) -> (String, Value) {
+    let (status, created) = send(
+        app,
+        create(serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": ttl_secs,
+            "grace_secs": grace_secs,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let (status, first) = send(app, reveal(&id, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    (id, first)
+}
+
+#[tokio::test]
+async fn claimed_copy_is_only_reachable_with_the_claim_token() {
+    let app = app_router_with_in_memory_store();
+    let (id, first) = read_with_grace(&app, 60, 30).await;
+    let claim_token = first["claim_token"].as_str().unwrap();
+    let copy_id = claim_id(&id, claim_token);
+
+    let (status, _) = send(&app, reveal(&copy_id, None)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    for uri in [
+        format!("/api/secret/{copy_id}"),
+        format!("/api/secret/{copy_id}/meta"),
+    ] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    let request = Request::builder()
+        .method("DELETE")
+        .uri(format!("/api/secret/{copy_id}"))
+        .header("authorization", "Bearer anything")
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send(&app, request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // None of that used the copy up.
+    let (status, again) = send(&app, reveal(&id, Some(claim_token))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again["ciphertext"], "ciphertext-value");
+}
+
+#[tokio::test]
+async fn claimed_copy_never_outlives_the_secret() {
+    let app = app_router_with_in_memory_store();
+    let (id, first) = read_with_grace(&app, 2, 30).await;
+    let claim_token = first["claim_token"].as_str().unwrap();
+    let at = |field: &str| {
+        OffsetDateTime::parse(first[field].as_str().unwrap(), &Rfc3339)
+            .expect("timestamps should be RFC 3339")
+    };
+    assert!(at("claim_expires_at") <= at("expires_at"));
+
+    tokio::time::sleep(StdDuration::from_millis(2100)).await;
+    let (status, _) = send(&app, reveal(&id, Some(claim_token))).await;
+    assert_eq!(status, StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn grace_window_is_bounded_and_single_view_only() {
+    let app = app_router_with_in_memory_store();
+
+    for (grace_secs, max_views) in [(0u32, 1u32), (301, 1), (30, 2)] {
+        let (status, _) = send(
+            &app,
+            create(serde_json::json!({
+                "ciphertext": "ciphertext-value",
+                "iv": "iv-value",
+                "ttl_secs": 60u32,
+                "grace_secs": grace_secs,
+                "max_views": max_views,
+            })),
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "grace {grace_secs}, views {max_views}"
+        );
+    }
+}
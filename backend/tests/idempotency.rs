@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::{InMemorySecretStore, SecretStore, StorageResult};
+use cendre_backend::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+use cendre_backend::token::{hash_token, open_token, seal_token};
+use cendre_backend::{
+    ApiConfig, app_router_with_in_memory_store, app_router_with_store,
+    app_router_with_store_and_config,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::OffsetDateTime;
+use tokio::task::JoinSet;
+use tower::ServiceExt; // for `oneshot`
+
+/// An in-memory store where a concurrent retry of the same request always
+/// saves its idempotency record, `winner`, first. The record that lost is
+/// kept for inspection.
+struct RacedStore {
+    inner: InMemorySecretStore,
+    winner: IdempotencyRecord,
+    loser: Mutex<Option<IdempotencyRecord>>,
+}
+
+#[async_trait]
+impl SecretStore for RacedStore {
+    async fn insert_secret(&self, secret: Secret) -> StorageResult<Secret> {
+        self.inner.insert_secret(secret).await
+    }
+
+    async fn get_and_delete_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        self.inner.get_and_delete_secret(id).await
+    }
+
+    async fn peek_secret(&self, id: &str) -> StorageResult<Option<Secret>> {
+        self.inner.peek_secret(id).await
+    }
+
+    async fn record_failed_pin(&self, id: &str, max_attempts: u32) -> StorageResult<Option<u32>> {
+        self.inner.record_failed_pin(id, max_attempts).await
+    }
+
+    async fn burn_secret(&self, id: &str) -> StorageResult<bool> {
+        self.inner.burn_secret(id).await
+    }
+
+    async fn set_secret_ttl(&self, id: &str, ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        self.inner.set_secret_ttl(id, ttl_secs).await
+    }
+
+    async fn put_tombstone(&self, id: &str, tombstone: &Tombstone) -> StorageResult<()> {
+        self.inner.put_tombstone(id, tombstone).await
+    }
+
+    async fn get_tombstone(&self, id: &str) -> StorageResult<Option<Tombstone>> {
+        self.inner.get_tombstone(id).await
+    }
+
+    async fn put_receipt(&self, receipt: &Receipt) -> StorageResult<()> {
+        self.inner.put_receipt(receipt).await
+    }
+
+    async fn get_receipt(&self, id: &str) -> StorageResult<Option<Receipt>> {
+        self.inner.get_receipt(id).await
+    }
+
+    async fn delete_receipt(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_receipt(id).await
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        let winner = IdempotencyRecord {
+            request_hash: record.request_hash.clone(),
+            ..self.winner.clone()
+        };
+        self.inner.put_idempotency_record(key, &winner).await?;
+        *self.loser.lock().unwrap() = Some(record.clone());
+        self.inner.put_idempotency_record(key, record).await
+    }
+
+    async fn get_idempotency_record(&self, key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        self.inner.get_idempotency_record(key).await
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        key: &str,
+        record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        self.inner.replace_idempotency_record(key, record).await
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        self.inner.ping().await
+    }
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn create(idempotency_key: Option<&str>, ciphertext: &str) -> Request<Body> {
+    let payload = serde_json::json!({
+        "ciphertext": ciphertext,
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/secrets")
+        .header("content-type", "application/json");
+    if let Some(key) = idempotency_key {
+        request = request.header("idempotency-key", key);
+    }
+    request
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request")
+}
+
+fn reveal(id: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/secret/{id}/reveal"))
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn retry_with_the_same_key_returns_the_same_secret() {
+    let app = app_router_with_in_memory_store();
+
+    let (status, first) = send(&app, create(Some("ci-run-42"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, retry) = send(&app, create(Some("ci-run-42"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retry, first);
+
+    // Only one secret was created.
+    let id = first["id"].as_str().unwrap();
+    let (status, _) = send(&app, reveal(id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, reveal(id)).await;
+    assert_eq!(status, StatusCode::GONE);
+
+    // Without a key, every request makes a new secret.
+    let (_, a) = send(&app, create(None, "ciphertext-value")).await;
+    let (_, b) = send(&app, create(None, "ciphertext-value")).await;
+    assert_ne!(a["id"], b["id"]);
+}
+
+#[tokio::test]
+async fn reusing_a_key_for_a_different_request_is_a_conflict() {
+    let app = app_router_with_in_memory_store();
+
+    let (status, _) = send(&app, create(Some("ci-run-43"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(&app, create(Some("ci-run-43"), "other-ciphertext")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["error"].as_str().unwrap().contains("Idempotency-Key"));
+
+    let (status, _) = send(&app, create(Some(&"k".repeat(256)), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn concurrent_retries_agree_on_one_secret() {
+    let app = app_router_with_in_memory_store();
+
+    let mut requests = JoinSet::new();
+    for _ in 0..8 {
+        let app = app.clone();
+        requests
+            .spawn(async move { send(&app, create(Some("ci-run-44"), "ciphertext-value")).await });
+    }
+
+    let mut ids = HashSet::new();
+    while let Some(result) = requests.join_next().await {
+        let (status, body) = result.expect("request task should not panic");
+        assert_eq!(status, StatusCode::OK);
+        ids.insert(body["id"].as_str().unwrap().to_string());
+    }
+    assert_eq!(ids.len(), 1, "all retries should get the same secret");
+}
+
+#[tokio::test]
+async fn stored_records_keep_the_management_token_sealed() {
+    let store = Arc::new(InMemorySecretStore::new());
+    let app = app_router_with_store(store.clone());
+
+    let (status, created) = send(&app, create(Some("ci-run-47"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    let token = created["management_token"].as_str().unwrap();
+
+    let record = store
+        .get_idempotency_record(&hash_token("ci-run-47"))
+        .await
+        .unwrap()
+        .expect("the record should be stored under the key's hash");
+    let stored = serde_json::to_string(&record).unwrap();
+    assert!(
+        !stored.contains(token),
+        "the token must not be stored in the clear"
+    );
+    assert!(!stored.contains("ci-run-47"));
+    assert_eq!(
+        open_token(
+            &record.sealed_management_token,
+            "ci-run-47",
+            &record.secret_id
+        )
+        .as_deref(),
+        Some(token)
+    );
+}
+
+#[tokio::test]
+async fn losing_a_race_takes_back_the_secret_and_its_receipt() {
+    let now = OffsetDateTime::now_utc();
+    let store = Arc::new(RacedStore {
+        inner: InMemorySecretStore::new(),
+        winner: IdempotencyRecord {
+            request_hash: String::new(),
+            secret_id: "winner".into(),
+            sealed_management_token: seal_token("winner-token", "ci-run-48", "winner"),
+            receipt_id: None,
+            secret_expires_at: now + time::Duration::minutes(1),
+            expires_at: now + time::Duration::minutes(1),
+        },
+        loser: Mutex::new(None),
+    });
+    let app = app_router_with_store(store.clone());
+
+    let (status, body) = send(&app, create(Some("ci-run-48"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], "winner");
+    assert_eq!(body["management_token"], "winner-token");
+
+    let loser = store
+        .loser
+        .lock()
+        .unwrap()
+        .clone()
+        .expect("a record should have lost");
+    assert!(store.peek_secret(&loser.secret_id).await.unwrap().is_none());
+    assert!(
+        store
+            .get_tombstone(&loser.secret_id)
+            .await
+            .unwrap()
+            .is_none(),
+        "the losing secret should leave no trace"
+    );
+    let receipt_id = loser.receipt_id.expect("receipts are on by default");
+    assert!(store.get_receipt(&receipt_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn keys_are_forgotten_after_the_window() {
+    let config = ApiConfig {
+        idempotency_ttl: Duration::from_millis(200),
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config);
+
+    let (_, first) = send(&app, create(Some("ci-run-45"), "ciphertext-value")).await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let (status, later) = send(&app, create(Some("ci-run-45"), "other-ciphertext")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(later["id"], first["id"]);
+
+    let disabled = ApiConfig {
+        idempotency_ttl: Duration::ZERO,
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), disabled);
+    let (_, a) = send(&app, create(Some("ci-run-46"), "ciphertext-value")).await;
+    let (_, b) = send(&app, create(Some("ci-run-46"), "ciphertext-value")).await;
+    assert_ne!(a["id"], b["id"]);
+}
+
+#[tokio::test]
+async fn ttl_updates_carry_over_to_retries() {
+    let config = ApiConfig {
+        idempotency_ttl: Duration::from_millis(200),
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config);
+
+    let (_, created) = send(&app, create(Some("ci-run-48"), "ciphertext-value")).await;
+    let id = created["id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+
+    let (status, updated) = send(
+        &app,
+        Request::builder()
+            .method("PATCH")
+            .uri(format!("/api/secret/{id}"))
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::from(
+                serde_json::json!({ "ttl_secs": 120u32 }).to_string(),
+            ))
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The record now lives as long as the secret, past the usual window.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let (status, retry) = send(&app, create(Some("ci-run-48"), "ciphertext-value")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retry["id"], created["id"]);
+    assert_eq!(retry["expires_at"], updated["expires_at"]);
+}
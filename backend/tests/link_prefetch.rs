@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::InMemorySecretStore;
+use cendre_backend::{
+    ApiConfig, app_router_with_in_memory_store, app_router_with_store_and_config,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt; // for `oneshot`
+
+async fn create_secret(app: &Router) -> String {
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    json["id"]
+        .as_str()
+        .expect("response should contain an id")
+        .to_string()
+}
+
+async fn send(app: &Router, method: &str, uri: String) -> (StatusCode, Vec<u8>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn head_and_get_prefetches_leave_the_secret_intact() {
+    let app = app_router_with_in_memory_store();
+    let id = create_secret(&app).await;
+
+    // What a link unfurler or mail scanner would do before a human clicks.
+    for method in ["HEAD", "GET", "GET", "HEAD"] {
+        let (status, body) = send(&app, method, format!("/api/secret/{id}")).await;
+        assert_eq!(status, StatusCode::OK, "{method} prefetch should succeed");
+
+        if method == "GET" {
+            let json: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["id"], id.as_str());
+            assert!(json.get("ciphertext").is_none() && json.get("iv").is_none());
+        } else {
+            assert!(body.is_empty());
+        }
+    }
+
+    let (status, body) = send(&app, "POST", format!("/api/secret/{id}/reveal")).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["ciphertext"], "ciphertext-value");
+    assert_eq!(json["iv"], "iv-value");
+
+    for method in ["HEAD", "GET"] {
+        let (status, _) = send(&app, method, format!("/api/secret/{id}")).await;
+        assert_eq!(status, StatusCode::GONE);
+    }
+    let (status, _) = send(&app, "POST", format!("/api/secret/{id}/reveal")).await;
+    assert_eq!(status, StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn legacy_consuming_get_can_be_turned_back_on() {
+    let config = ApiConfig {
+        legacy_consuming_get: true,
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config);
+    let id = create_secret(&app).await;
+
+    // HEAD never consumes, even in legacy mode.
+    let (status, _) = send(&app, "HEAD", format!("/api/secret/{id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "GET", format!("/api/secret/{id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["ciphertext"], "ciphertext-value");
+
+    let (status, _) = send(&app, "GET", format!("/api/secret/{id}")).await;
+    assert_eq!(status, StatusCode::GONE);
+}
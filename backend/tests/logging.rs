@@ -107,8 +107,8 @@ async fn logs_do_not_contain_ciphertext_or_iv() {
     let read_response = app
         .oneshot(
             Request::builder()
-                .method("GET")
-                .uri(format!("/api/secret/{id}"))
+                .method("POST")
+                .uri(format!("/api/secret/{id}/reveal"))
                 .body(Body::empty())
                 .expect("failed to build request"),
         )
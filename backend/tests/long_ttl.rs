@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::{SecretStore, SqliteSecretStore};
+use cendre_backend::{app_router_with_in_memory_store, app_router_with_store};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use tower::ServiceExt; // for `oneshot`
+
+const WEEK_SECS: u32 = 7 * 24 * 60 * 60;
+
+struct TempDb {
+    path: std::path::PathBuf,
+}
+
+impl TempDb {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("cendre-test-{}.db", uuid::Uuid::new_v4()));
+        Self { path }
+    }
+
+    fn url(&self) -> String {
+        format!("sqlite://{}", self.path.display())
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-journal"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+        }
+    }
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn create(ttl_secs: u32) -> Request<Body> {
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": ttl_secs,
+    });
+    Request::builder()
+        .method("POST")
+        .uri("/api/secrets")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request")
+}
+
+fn ttl_policy() -> Request<Body> {
+    Request::builder()
+        .uri("/api/ttl-policy")
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn long_tier_is_refused_on_a_volatile_store() {
+    let app = app_router_with_in_memory_store();
+
+    let (status, policy) = send(&app, ttl_policy()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(policy["long_tier_available"], false);
+
+    let (status, body) = send(&app, create(WEEK_SECS)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("durable"));
+
+    // The short tier is unaffected.
+    let (status, _) = send(&app, create(86_400)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn embargo_cannot_stretch_a_secret_into_the_long_tier_on_a_volatile_store() {
+    let app = app_router_with_in_memory_store();
+    let embargoed = |days: i64| {
+        let not_before = time::OffsetDateTime::now_utc() + time::Duration::days(days);
+        let payload = serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "ttl_secs": 86_400u32,
+            "not_before": not_before.format(&Rfc3339).unwrap(),
+            "ttl_start": "not_before",
+        });
+        Request::builder()
+            .method("POST")
+            .uri("/api/secrets")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("failed to build request")
+    };
+
+    // A day's TTL counted from 29 days out keeps the secret for 30 days.
+    let (status, body) = send(&app, embargoed(29)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("durable"));
+
+    let (status, _) = send(&app, embargoed(0)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn long_tier_is_allowed_on_a_durable_store() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+    assert!(store.is_durable().await.unwrap());
+    let app = app_router_with_store(Arc::new(store));
+
+    let (status, policy) = send(&app, ttl_policy()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(policy["long_tier_available"], true);
+    assert_eq!(policy["long_max_secs"], 30 * 24 * 60 * 60);
+
+    let (status, created) = send(&app, create(WEEK_SECS)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Extending an existing secret into the long tier works too, up to the cap.
+    let id = created["id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+    for (ttl_secs, expected) in [
+        (30 * 24 * 60 * 60, StatusCode::OK),
+        (30 * 24 * 60 * 60 + 1, StatusCode::BAD_REQUEST),
+    ] {
+        let (status, _) = send(
+            &app,
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/secret/{id}"))
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::from(
+                    serde_json::json!({ "ttl_secs": ttl_secs }).to_string(),
+                ))
+                .expect("failed to build request"),
+        )
+        .await;
+        assert_eq!(status, expected, "for ttl_secs {ttl_secs}");
+    }
+}
+
+#[tokio::test]
+async fn in_memory_sqlite_is_not_durable() {
+    let store = SqliteSecretStore::new("sqlite::memory:")
+        .await
+        .expect("sqlite store should open");
+    assert!(!store.is_durable().await.unwrap());
+}
@@ -0,0 +1,420 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use cendre_backend::db::{PostgresSecretStore, SecretStore};
+use cendre_backend::models::{
+    IdempotencyRecord, Receipt, ReceiptStatus, Secret, Tombstone, TombstoneReason,
+};
+use tokio::task::JoinSet;
+use tokio::time::sleep;
+
+fn postgres_url_from_env() -> Option<String> {
+    env::var("POSTGRES_URL")
+        .ok()
+        .or_else(|| env::var("TEST_POSTGRES_URL").ok())
+}
+
+async fn create_store() -> Option<PostgresSecretStore> {
+    let url = match postgres_url_from_env() {
+        Some(url) => url,
+        None => {
+            eprintln!(
+                "POSTGRES_URL or TEST_POSTGRES_URL not set; skipping Postgres integration tests"
+            );
+            return None;
+        }
+    };
+
+    match PostgresSecretStore::new(&url).await {
+        Ok(store) => Some(store),
+        Err(err) => {
+            eprintln!(
+                "Failed to connect to Postgres at {}: {:?}; skipping tests",
+                url, err
+            );
+            None
+        }
+    }
+}
+
+#[tokio::test]
+async fn get_and_delete_secret_is_one_time_with_postgres() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let first = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("first get_and_delete_secret should succeed")
+        .expect("first read should return the secret");
+    assert_eq!(first.ciphertext, created.ciphertext);
+    assert_eq!(first.iv, created.iv);
+
+    let second = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("second get_and_delete_secret should also succeed");
+    assert!(
+        second.is_none(),
+        "second read should not find the secret after deletion"
+    );
+}
+
+#[tokio::test]
+async fn expired_secrets_are_not_returned() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+
+    sleep(StdDuration::from_millis(1100)).await;
+
+    let fetched = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("get_and_delete_secret should succeed after ttl");
+    assert!(fetched.is_none(), "expired secret must not be returned");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_only_one_receives_the_secret() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+        });
+    }
+
+    let mut winners = 0;
+    while let Some(result) = tasks.join_next().await {
+        if result.expect("reader task should not panic").is_some() {
+            winners += 1;
+        }
+    }
+    assert_eq!(
+        winners, 1,
+        "exactly one concurrent reader should get the secret"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_share_a_multi_view_secret() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+                .map(|secret| secret.remaining_views)
+        });
+    }
+
+    let mut remaining = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        remaining.extend(result.expect("reader task should not panic"));
+    }
+    remaining.sort_unstable();
+    assert_eq!(
+        remaining,
+        vec![0, 1, 2],
+        "each of the three views should be handed out exactly once"
+    );
+}
+
+#[tokio::test]
+async fn wrong_pins_burn_the_secret() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(
+            Secret::new("ciphertext".into(), "iv".into(), 60).with_pin_hash("pin-hash".into()),
+        )
+        .await
+        .expect("insert_secret should succeed");
+
+    let peeked = store
+        .peek_secret(&created.id)
+        .await
+        .expect("peek_secret should succeed")
+        .expect("peek should find the secret");
+    assert_eq!(peeked.pin_hash.as_deref(), Some("pin-hash"));
+
+    for expected_left in [2, 1, 0] {
+        let left = store
+            .record_failed_pin(&created.id, 3)
+            .await
+            .expect("record_failed_pin should succeed");
+        assert_eq!(left, Some(expected_left));
+    }
+
+    assert!(
+        store
+            .peek_secret(&created.id)
+            .await
+            .expect("peek_secret should succeed")
+            .is_none(),
+        "secret should be burned after the last wrong pin"
+    );
+}
+
+#[tokio::test]
+async fn tombstone_replaces_earlier_one_and_expires() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+    let now = time::OffsetDateTime::now_utc();
+    let expired = Tombstone::new(TombstoneReason::Expired, now + time::Duration::minutes(5));
+    let mut read = Tombstone::new(TombstoneReason::Read, now + time::Duration::minutes(5));
+    read.read_at = Some(now);
+
+    store
+        .put_tombstone("tombstoned", &expired)
+        .await
+        .expect("put_tombstone should succeed");
+    store
+        .put_tombstone("tombstoned", &read)
+        .await
+        .expect("put_tombstone should replace the earlier tombstone");
+    let found = store
+        .get_tombstone("tombstoned")
+        .await
+        .expect("get_tombstone should succeed");
+    assert_eq!(found, Some(read));
+
+    store
+        .put_tombstone(
+            "stale",
+            &Tombstone::new(TombstoneReason::Revoked, now - time::Duration::seconds(1)),
+        )
+        .await
+        .expect("put_tombstone should succeed");
+    assert!(
+        store
+            .get_tombstone("stale")
+            .await
+            .expect("get_tombstone should succeed")
+            .is_none(),
+        "an expired tombstone should not be returned"
+    );
+}
+
+#[tokio::test]
+async fn receipt_outlives_its_secret() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let secret = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60))
+        .await
+        .expect("insert_secret should succeed");
+    let mut receipt = Receipt::new(
+        uuid::Uuid::new_v4().simple().to_string(),
+        &secret,
+        secret.expires_at() + time::Duration::hours(1),
+    );
+    store
+        .put_receipt(&receipt)
+        .await
+        .expect("put_receipt should succeed");
+
+    assert!(
+        store
+            .burn_secret(&secret.id)
+            .await
+            .expect("burn should succeed")
+    );
+    assert!(receipt.settle(ReceiptStatus::Revoked, None, None));
+    store
+        .put_receipt(&receipt)
+        .await
+        .expect("put_receipt should replace the earlier receipt");
+
+    let found = store
+        .get_receipt(&receipt.id)
+        .await
+        .expect("get_receipt should succeed");
+    assert_eq!(found, Some(receipt));
+}
+
+#[tokio::test]
+async fn set_secret_ttl_moves_the_expiry() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 1).with_max_views(2))
+        .await
+        .expect("insert_secret should succeed");
+    store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed")
+        .expect("first view should be served");
+
+    let updated = store
+        .set_secret_ttl(&created.id, 60)
+        .await
+        .expect("set_secret_ttl should succeed")
+        .expect("secret should be found");
+    assert_eq!(updated.ttl_secs, 60);
+    assert_eq!(updated.remaining_views, 1);
+    assert_eq!(
+        updated.expires_at(),
+        created.created_at + time::Duration::seconds(60)
+    );
+
+    // Well past the original one-second TTL.
+    sleep(StdDuration::from_millis(1500)).await;
+    let read = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert_eq!(read.map(|secret| secret.remaining_views), Some(0));
+
+    assert!(
+        store
+            .set_secret_ttl(&created.id, 60)
+            .await
+            .expect("set_secret_ttl should succeed")
+            .is_none(),
+        "a consumed secret cannot be extended"
+    );
+}
+
+#[tokio::test]
+async fn postgres_store_is_durable() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    assert!(store.is_durable().await.expect("is_durable should succeed"));
+}
+
+#[tokio::test]
+async fn idempotency_record_is_kept_until_it_expires() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let record = |secret_id: &str, expires_in: time::Duration| IdempotencyRecord {
+        request_hash: "request-hash".into(),
+        secret_id: secret_id.into(),
+        sealed_management_token: "sealed-management-token".into(),
+        receipt_id: None,
+        secret_expires_at: time::OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        expires_at: time::OffsetDateTime::now_utc() + expires_in,
+    };
+    let key = uuid::Uuid::new_v4().simple().to_string();
+
+    let first = record("first", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&key, &first)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+    // A live record is never replaced, only reported.
+    let existing = store
+        .put_idempotency_record(&key, &record("second", time::Duration::minutes(1)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(existing.as_ref(), Some(&first));
+    assert_eq!(
+        store
+            .get_idempotency_record(&key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        Some(first)
+    );
+
+    // An expired one may be.
+    let other_key = uuid::Uuid::new_v4().simple().to_string();
+    store
+        .put_idempotency_record(&other_key, &record("stale", time::Duration::seconds(-5)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(
+        store
+            .get_idempotency_record(&other_key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        None
+    );
+    let fresh = record("fresh", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&other_key, &fresh)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+}
+
+#[tokio::test]
+async fn sweeper_stops_once_the_store_is_dropped() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(10));
+    sleep(StdDuration::from_millis(50)).await;
+
+    drop(store);
+    tokio::time::timeout(StdDuration::from_secs(1), sweeper)
+        .await
+        .expect("sweeper should stop once the store is dropped")
+        .expect("sweeper task should not panic");
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::InMemorySecretStore;
+use cendre_backend::{
+    ApiConfig, app_router_with_in_memory_store, app_router_with_store_and_config,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt; // for `oneshot`
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, json)
+}
+
+async fn create_secret(app: &Router) -> Value {
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/secrets")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request");
+
+    let (status, json) = send(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    json
+}
+
+fn get(uri: String) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+fn reveal(id: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/secret/{id}/reveal"))
+        .header(
+            "user-agent",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0",
+        )
+        .body(Body::empty())
+        .expect("failed to build request")
+}
+
+#[tokio::test]
+async fn receipt_reports_when_the_secret_was_read() {
+    let app = app_router_with_in_memory_store();
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+    let receipt_id = created["receipt_id"]
+        .as_str()
+        .expect("response should contain a receipt id");
+    assert_ne!(receipt_id, id);
+
+    let (status, receipt) = send(&app, get(format!("/api/receipt/{receipt_id}"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(receipt["status"], "pending");
+    assert!(receipt.get("read_at").is_none());
+    assert!(receipt["secret_expires_at"].is_string());
+
+    let (status, _) = send(&app, reveal(id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, receipt) = send(&app, get(format!("/api/receipt/{receipt_id}"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(receipt["status"], "read");
+    assert!(receipt["read_at"].is_string());
+    // Client hints are off unless configured.
+    assert!(receipt.get("client_hint").is_none());
+    assert!(receipt.get("ciphertext").is_none() && receipt.get("iv").is_none());
+
+    // A receipt id does not address the secret, and vice versa.
+    let (status, _) = send(&app, get(format!("/api/secret/{receipt_id}"))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, get(format!("/api/receipt/{id}"))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn receipt_records_the_browser_family_when_enabled() {
+    let config = ApiConfig {
+        receipt_client_hints: true,
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config);
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+    let receipt_id = created["receipt_id"].as_str().unwrap();
+
+    let (status, _) = send(&app, reveal(id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, receipt) = send(&app, get(format!("/api/receipt/{receipt_id}"))).await;
+    assert_eq!(receipt["status"], "read");
+    assert_eq!(receipt["client_hint"], "Firefox");
+}
+
+#[tokio::test]
+async fn receipt_reports_revocation() {
+    let app = app_router_with_in_memory_store();
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+    let receipt_id = created["receipt_id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri(format!("/api/secret/{id}"))
+        .header("authorization", format!("Bearer {token}"))
+        .body(Body::empty())
+        .expect("failed to build request");
+    let (status, _) = send(&app, request).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_, receipt) = send(&app, get(format!("/api/receipt/{receipt_id}"))).await;
+    assert_eq!(receipt["status"], "revoked");
+    assert!(receipt.get("read_at").is_none());
+}
+
+#[tokio::test]
+async fn receipts_can_be_turned_off() {
+    let config = ApiConfig {
+        receipt_ttl: std::time::Duration::ZERO,
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config);
+    let created = create_secret(&app).await;
+
+    assert!(created.get("receipt_id").is_none());
+}
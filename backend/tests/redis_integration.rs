@@ -1,8 +1,19 @@
 use std::env;
+use std::sync::Arc;
 use std::time::Duration as StdDuration;
 
-use cendre_backend::db::{RedisSecretStore, SecretStore};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::app_router_with_store;
+use cendre_backend::db::{
+    RedisPoolConfig, RedisSecretStore, RedisSecurity, RedisTopology, SecretStore,
+};
+use cendre_backend::models::{IdempotencyRecord, Secret, Tombstone, TombstoneReason};
+use tokio::task::JoinSet;
 use tokio::time::sleep;
+use tower::ServiceExt; // for `oneshot`
 
 fn redis_url_from_env() -> Option<String> {
     env::var("REDIS_URL")
@@ -111,3 +122,468 @@ async fn secrets_expire_after_ttl() {
         "secret should no longer be available after TTL has elapsed"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_only_one_receives_the_secret() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let store: Arc<dyn SecretStore> = Arc::new(store);
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let app = app_router_with_store(store);
+    let readers = 32;
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..readers {
+        let app = app.clone();
+        let uri = format!("/api/secret/{}/reveal", created.id);
+        tasks.spawn(async move {
+            app.oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request to router should succeed")
+            .status()
+        });
+    }
+
+    let mut ok = 0;
+    let mut gone = 0;
+    while let Some(status) = tasks.join_next().await {
+        match status.expect("reader task should not panic") {
+            StatusCode::OK => ok += 1,
+            StatusCode::GONE => gone += 1,
+            other => panic!("unexpected status from concurrent read: {other}"),
+        }
+    }
+
+    assert_eq!(ok, 1, "exactly one concurrent reader should get the secret");
+    assert_eq!(gone, readers - 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pool_serves_concurrent_requests_and_reports_stats() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let store = Arc::new(store);
+    let mut tasks = JoinSet::new();
+    for _ in 0..64 {
+        let store = store.clone();
+        tasks.spawn(async move {
+            store
+                .store_secret("ciphertext".into(), "iv".into(), 10)
+                .await
+                .expect("store_secret should succeed under concurrency")
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result.expect("writer task should not panic");
+    }
+
+    let stats = store
+        .pool_stats()
+        .expect("RedisSecretStore should report pool stats");
+    assert!(stats.connections >= 1);
+    assert!(stats.connections <= stats.max_size);
+    assert_eq!(stats.gets_timed_out, 0);
+}
+
+async fn roundtrip_once(store: &RedisSecretStore) {
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let first = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("first get_and_delete_secret should succeed");
+    assert!(first.is_some(), "first read should return the secret");
+
+    let second = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("second get_and_delete_secret should succeed");
+    assert!(second.is_none(), "second read should not find the secret");
+}
+
+fn node_list(raw: &str) -> Vec<String> {
+    raw.split(',').map(|node| node.trim().to_string()).collect()
+}
+
+#[tokio::test]
+async fn sentinel_topology_serves_one_time_reads() {
+    let Ok(sentinels) = env::var("TEST_REDIS_SENTINELS") else {
+        eprintln!("TEST_REDIS_SENTINELS not set; skipping Redis Sentinel test");
+        return;
+    };
+
+    let topology = RedisTopology::Sentinel {
+        sentinels: node_list(&sentinels),
+        master_name: env::var("TEST_REDIS_SENTINEL_MASTER")
+            .unwrap_or_else(|_| "mymaster".to_string()),
+        db: 0,
+    };
+    let store = RedisSecretStore::connect(
+        topology,
+        RedisSecurity::default(),
+        "secret:",
+        RedisPoolConfig::default(),
+    )
+    .await
+    .expect("should connect to the master through Sentinel");
+
+    roundtrip_once(&store).await;
+}
+
+#[tokio::test]
+async fn cluster_topology_serves_one_time_reads() {
+    let Ok(nodes) = env::var("TEST_REDIS_CLUSTER_NODES") else {
+        eprintln!("TEST_REDIS_CLUSTER_NODES not set; skipping Redis Cluster test");
+        return;
+    };
+
+    let topology = RedisTopology::Cluster {
+        nodes: node_list(&nodes),
+    };
+    let store = RedisSecretStore::connect(
+        topology,
+        RedisSecurity::default(),
+        "secret:",
+        RedisPoolConfig::default(),
+    )
+    .await
+    .expect("should connect to the cluster through its seed nodes");
+
+    roundtrip_once(&store).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_share_a_multi_view_secret() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+                .map(|secret| secret.remaining_views)
+        });
+    }
+
+    let mut remaining = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        remaining.extend(result.expect("reader task should not panic"));
+    }
+    remaining.sort_unstable();
+    assert_eq!(
+        remaining,
+        vec![0, 1, 2],
+        "each of the three views should be handed out exactly once"
+    );
+}
+
+#[tokio::test]
+async fn wrong_pins_burn_the_secret() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(
+            Secret::new("ciphertext".into(), "iv".into(), 60).with_pin_hash("pin-hash".into()),
+        )
+        .await
+        .expect("insert_secret should succeed");
+
+    let peeked = store
+        .peek_secret(&created.id)
+        .await
+        .expect("peek_secret should succeed")
+        .expect("peek should find the secret");
+    assert_eq!(peeked.pin_hash.as_deref(), Some("pin-hash"));
+
+    for expected_left in [2, 1, 0] {
+        let left = store
+            .record_failed_pin(&created.id, 3)
+            .await
+            .expect("record_failed_pin should succeed");
+        assert_eq!(left, Some(expected_left));
+    }
+
+    assert!(
+        store
+            .peek_secret(&created.id)
+            .await
+            .expect("peek_secret should succeed")
+            .is_none(),
+        "secret should be burned after the last wrong pin"
+    );
+}
+
+#[tokio::test]
+async fn burn_secret_removes_every_remaining_view() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    assert!(
+        store
+            .burn_secret(&created.id)
+            .await
+            .expect("burn should succeed")
+    );
+    assert!(
+        store
+            .get_and_delete_secret(&created.id)
+            .await
+            .expect("read should succeed")
+            .is_none()
+    );
+    assert!(
+        !store
+            .burn_secret(&created.id)
+            .await
+            .expect("burn should succeed")
+    );
+}
+
+#[tokio::test]
+async fn metadata_reports_redis_ttl_without_consuming() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(2))
+        .await
+        .expect("insert_secret should succeed");
+
+    let metadata = store
+        .secret_metadata(&created.id)
+        .await
+        .expect("secret_metadata should succeed")
+        .expect("metadata should be found");
+    assert_eq!(metadata.ciphertext_len, "ciphertext".len());
+    assert_eq!(metadata.remaining_views, 2);
+    assert!(metadata.expires_at <= created.expires_at() + time::Duration::seconds(1));
+    assert!(metadata.expires_at > created.created_at);
+
+    let read = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert_eq!(read.map(|secret| secret.remaining_views), Some(1));
+}
+
+#[tokio::test]
+async fn ids_that_would_break_hash_tags_are_not_found() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+    assert!(store.peek_secret("a}b{c").await.unwrap().is_none());
+    assert!(!store.burn_secret("{abc}").await.unwrap());
+
+    // On a cluster these ids would otherwise span slots and fail with CROSSSLOT.
+    let app = app_router_with_store(store);
+    for uri in ["/api/secret/a%7Db%7Bc/meta", "/api/secret/%7Babc%7D/reveal"] {
+        let method = if uri.ends_with("reveal") {
+            "POST"
+        } else {
+            "GET"
+        };
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request to router should succeed");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn tombstone_outlives_the_secret_with_its_own_ttl() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 1))
+        .await
+        .expect("insert_secret should succeed");
+    let read = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed")
+        .expect("secret should be found");
+
+    let tombstone = Tombstone::read(
+        &read,
+        time::OffsetDateTime::now_utc() + time::Duration::seconds(30),
+    );
+    store
+        .put_tombstone(&created.id, &tombstone)
+        .await
+        .expect("put_tombstone should succeed");
+
+    // Well past the secret's own one-second TTL.
+    sleep(StdDuration::from_millis(1500)).await;
+    let found = store
+        .get_tombstone(&created.id)
+        .await
+        .expect("get_tombstone should succeed");
+    assert_eq!(found, Some(tombstone));
+    assert_eq!(found.unwrap().reason, TombstoneReason::Read);
+}
+
+#[tokio::test]
+async fn set_secret_ttl_moves_the_expiry_of_every_key() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 1).with_max_views(2))
+        .await
+        .expect("insert_secret should succeed");
+    store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed")
+        .expect("first view should be served");
+
+    let updated = store
+        .set_secret_ttl(&created.id, 60)
+        .await
+        .expect("set_secret_ttl should succeed")
+        .expect("secret should be found");
+    assert_eq!(updated.ttl_secs, 60);
+    assert_eq!(updated.remaining_views, 1);
+    assert_eq!(
+        updated.expires_at(),
+        created.created_at + time::Duration::seconds(60)
+    );
+
+    // Well past the original one-second TTL.
+    sleep(StdDuration::from_millis(1500)).await;
+    let read = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert_eq!(read.map(|secret| secret.remaining_views), Some(0));
+
+    assert!(
+        store
+            .set_secret_ttl(&created.id, 60)
+            .await
+            .expect("set_secret_ttl should succeed")
+            .is_none(),
+        "a consumed secret cannot be extended"
+    );
+}
+
+#[tokio::test]
+async fn durability_follows_the_servers_aof_setting() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let url = redis_url_from_env().expect("create_store checked the URL");
+    let mut conn = redis::Client::open(url)
+        .expect("valid Redis URL")
+        .get_multiplexed_async_connection()
+        .await
+        .expect("connect to Redis");
+    let config: Vec<String> = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("appendonly")
+        .query_async(&mut conn)
+        .await
+        .expect("CONFIG GET should succeed");
+
+    let durable = store.is_durable().await.expect("is_durable should succeed");
+    assert_eq!(durable, config.get(1).map(String::as_str) == Some("yes"));
+}
+
+#[tokio::test]
+async fn idempotency_record_is_set_only_once() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let record = |secret_id: &str, expires_in: time::Duration| IdempotencyRecord {
+        request_hash: "request-hash".into(),
+        secret_id: secret_id.into(),
+        sealed_management_token: "sealed-management-token".into(),
+        receipt_id: None,
+        secret_expires_at: time::OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        expires_at: time::OffsetDateTime::now_utc() + expires_in,
+    };
+    let key = uuid::Uuid::new_v4().simple().to_string();
+
+    let first = record("first", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&key, &first)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+    // A live record is never replaced, only reported.
+    let existing = store
+        .put_idempotency_record(&key, &record("second", time::Duration::minutes(1)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(existing.as_ref(), Some(&first));
+    assert_eq!(
+        store
+            .get_idempotency_record(&key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        Some(first)
+    );
+}
@@ -0,0 +1,227 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use cendre_backend::db::{S3SecretStore, S3StoreConfig, SecretStore};
+use cendre_backend::models::{Secret, Tombstone, TombstoneReason};
+use tokio::task::JoinSet;
+use tokio::time::sleep;
+
+/// Connection settings for an S3-compatible endpoint such as the MinIO service
+/// in docker-compose. The bucket must already exist.
+fn s3_config_from_env() -> Option<S3StoreConfig> {
+    let endpoint = env::var("TEST_S3_ENDPOINT").ok()?;
+
+    Some(S3StoreConfig {
+        endpoint: Some(endpoint),
+        bucket: env::var("TEST_S3_BUCKET").unwrap_or_else(|_| "cendre".to_string()),
+        region: "us-east-1".to_string(),
+        access_key_id: env::var("TEST_S3_ACCESS_KEY_ID")
+            .unwrap_or_else(|_| "minioadmin".to_string()),
+        secret_access_key: env::var("TEST_S3_SECRET_ACCESS_KEY")
+            .unwrap_or_else(|_| "minioadmin".to_string()),
+        key_prefix: format!("test-{}/", uuid::Uuid::new_v4()),
+        force_path_style: true,
+    })
+}
+
+async fn create_store() -> Option<S3SecretStore> {
+    let config = match s3_config_from_env() {
+        Some(config) => config,
+        None => {
+            eprintln!("TEST_S3_ENDPOINT not set; skipping S3 integration tests");
+            return None;
+        }
+    };
+
+    match S3SecretStore::new(config).await {
+        Ok(store) => Some(store),
+        Err(err) => {
+            eprintln!("Failed to reach S3 endpoint: {:?}; skipping tests", err);
+            None
+        }
+    }
+}
+
+#[tokio::test]
+async fn get_and_delete_secret_is_one_time_with_s3() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let first = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("first get_and_delete_secret should succeed")
+        .expect("first read should return the secret");
+    assert_eq!(first.ciphertext, created.ciphertext);
+    assert_eq!(first.iv, created.iv);
+
+    let second = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("second get_and_delete_secret should also succeed");
+    assert!(
+        second.is_none(),
+        "secret should be deleted after first read"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_only_one_receives_the_secret() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+        });
+    }
+
+    let mut winners = 0;
+    while let Some(result) = tasks.join_next().await {
+        if result.expect("reader task should not panic").is_some() {
+            winners += 1;
+        }
+    }
+    assert_eq!(
+        winners, 1,
+        "exactly one concurrent reader should get the secret"
+    );
+}
+
+#[tokio::test]
+async fn expired_objects_are_not_returned() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+
+    sleep(StdDuration::from_millis(1100)).await;
+
+    let fetched = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("get_and_delete_secret should succeed after ttl");
+    assert!(fetched.is_none(), "expired secret must not be returned");
+}
+
+#[tokio::test]
+async fn ids_outside_the_url_safe_alphabet_are_not_found() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let tombstone = Tombstone::new(
+        TombstoneReason::Revoked,
+        time::OffsetDateTime::now_utc() + time::Duration::minutes(1),
+    );
+    store
+        .put_tombstone("abc", &tombstone)
+        .await
+        .expect("put_tombstone should succeed");
+
+    // Without validation this id would address the tombstone's object.
+    let fetched = store
+        .get_and_delete_secret("tombstones/abc")
+        .await
+        .expect("lookup should succeed");
+    assert!(fetched.is_none());
+    assert!(!store.burn_secret("tombstones/abc").await.unwrap());
+    assert_eq!(store.get_tombstone("abc").await.unwrap(), Some(tombstone));
+}
+
+#[tokio::test]
+async fn sweeper_leaves_secrets_whose_expiry_moved_later() {
+    let store = match create_store().await {
+        Some(store) => store,
+        None => return,
+    };
+
+    let expired = store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+    let extended = store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+    store
+        .set_secret_ttl(&extended.id, 3600)
+        .await
+        .expect("set_secret_ttl should succeed")
+        .expect("secret should still be live");
+
+    sleep(StdDuration::from_millis(1100)).await;
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(100));
+    sleep(StdDuration::from_millis(500)).await;
+    sweeper.abort();
+
+    assert!(store.peek_secret(&expired.id).await.unwrap().is_none());
+    assert!(
+        store.peek_secret(&extended.id).await.unwrap().is_some(),
+        "the marker for the old expiry must not delete the secret"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_readers_share_a_multi_view_secret() {
+    let store = match create_store().await {
+        Some(store) => Arc::new(store),
+        None => return,
+    };
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+                .map(|secret| secret.remaining_views)
+        });
+    }
+
+    let mut remaining = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        remaining.extend(result.expect("reader task should not panic"));
+    }
+    remaining.sort_unstable();
+    assert_eq!(
+        remaining,
+        vec![0, 1, 2],
+        "each of the three views should be handed out exactly once"
+    );
+}
@@ -0,0 +1,565 @@
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::{SecretStore, SqliteSecretStore};
+use cendre_backend::models::{
+    IdempotencyRecord, Receipt, ReceiptStatus, Secret, Tombstone, TombstoneReason,
+};
+use cendre_backend::{ApiConfig, app_router_with_store, app_router_with_store_and_config};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::task::JoinSet;
+use tower::ServiceExt; // for `oneshot`
+
+struct TempDb {
+    path: std::path::PathBuf,
+}
+
+impl TempDb {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("cendre-test-{}.db", uuid::Uuid::new_v4()));
+        Self { path }
+    }
+
+    fn url(&self) -> String {
+        format!("sqlite://{}", self.path.display())
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-journal"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+        }
+    }
+}
+
+#[tokio::test]
+async fn sqlite_store_returns_secret_exactly_once() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("SqliteSecretStore should open a fresh database");
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 60)
+        .await
+        .expect("store_secret should succeed");
+
+    let first = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("first read should succeed")
+        .expect("secret should exist before it is read");
+    assert_eq!(first.ciphertext, "ciphertext");
+    assert_eq!(first.iv, "iv");
+    assert!(first.read_at.is_some());
+
+    let second = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("second read should succeed");
+    assert!(second.is_none(), "secret should be burned after first read");
+}
+
+#[tokio::test]
+async fn sqlite_store_survives_reopening() {
+    let db = TempDb::new();
+    let created = {
+        let store = SqliteSecretStore::new(&db.url())
+            .await
+            .expect("SqliteSecretStore should open a fresh database");
+        store
+            .store_secret("ciphertext".into(), "iv".into(), 60)
+            .await
+            .expect("store_secret should succeed")
+    };
+
+    let reopened = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("SqliteSecretStore should reopen an existing database");
+    let fetched = reopened
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert!(fetched.is_some(), "secret should survive a restart");
+}
+
+#[tokio::test]
+async fn sqlite_store_sweeper_purges_expired_secrets() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("SqliteSecretStore should open a fresh database");
+
+    let created = store
+        .store_secret("ciphertext".into(), "iv".into(), 1)
+        .await
+        .expect("store_secret should succeed");
+
+    tokio::time::sleep(StdDuration::from_millis(1100)).await;
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(50));
+    tokio::time::sleep(StdDuration::from_millis(200)).await;
+
+    let fetched = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert!(fetched.is_none(), "expired secret should have been purged");
+
+    drop(store);
+    tokio::time::timeout(StdDuration::from_secs(1), sweeper)
+        .await
+        .expect("sweeper should stop once the store is dropped")
+        .expect("sweeper task should not panic");
+}
+
+#[tokio::test]
+async fn sqlite_store_leaves_tombstones_only_once_secrets_are_gone() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("SqliteSecretStore should open a fresh database");
+
+    let mut expired = Secret::new("ciphertext".into(), "iv".into(), 1).with_tombstone_secs(300);
+    expired.created_at -= time::Duration::seconds(10);
+    let expired = store
+        .insert_secret(expired)
+        .await
+        .expect("insert_secret should succeed");
+    let live = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_tombstone_secs(300))
+        .await
+        .expect("insert_secret should succeed");
+
+    assert_eq!(store.get_tombstone(&live.id).await.unwrap(), None);
+    let before_sweep = store
+        .get_tombstone(&expired.id)
+        .await
+        .expect("get_tombstone should succeed")
+        .expect("an expired secret should report its tombstone");
+    assert_eq!(before_sweep.reason, TombstoneReason::Expired);
+
+    let sweeper = store.spawn_sweeper(StdDuration::from_millis(50));
+    tokio::time::sleep(StdDuration::from_millis(200)).await;
+    assert_eq!(
+        store.get_tombstone(&expired.id).await.unwrap(),
+        Some(before_sweep),
+        "the sweeper should leave the same tombstone behind"
+    );
+
+    store
+        .get_and_delete_secret(&live.id)
+        .await
+        .expect("read should succeed")
+        .expect("live secret should be returned");
+    let read = store
+        .get_tombstone(&live.id)
+        .await
+        .expect("get_tombstone should succeed")
+        .expect("reading the last view should leave a tombstone");
+    assert_eq!(read.reason, TombstoneReason::Read);
+
+    drop(store);
+    tokio::time::timeout(StdDuration::from_secs(1), sweeper)
+        .await
+        .expect("sweeper should stop once the store is dropped")
+        .expect("sweeper task should not panic");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn sqlite_store_releases_secret_to_one_of_many_concurrent_readers() {
+    let db = TempDb::new();
+    let store: Arc<dyn SecretStore> = Arc::new(
+        SqliteSecretStore::new(&db.url())
+            .await
+            .expect("SqliteSecretStore should open a fresh database"),
+    );
+    let app = app_router_with_store(store);
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/secrets")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request to router should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body collection should succeed")
+        .to_bytes();
+    let json: Value =
+        serde_json::from_slice(&body_bytes).expect("response body should be valid JSON");
+    let id = json
+        .get("id")
+        .and_then(|v| v.as_str())
+        .expect("response should contain an id")
+        .to_string();
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let app = app.clone();
+        let uri = format!("/api/secret/{id}/reveal");
+        tasks.spawn(async move {
+            app.oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request to router should succeed")
+            .status()
+        });
+    }
+
+    let mut ok = 0;
+    while let Some(status) = tasks.join_next().await {
+        let status = status.expect("reader task should not panic");
+        if status == StatusCode::OK {
+            ok += 1;
+        } else {
+            assert_eq!(status, StatusCode::GONE);
+        }
+    }
+    assert_eq!(ok, 1, "exactly one concurrent reader should get the secret");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn sqlite_store_hands_out_each_view_of_a_multi_view_secret_once() {
+    let db = TempDb::new();
+    let store = Arc::new(
+        SqliteSecretStore::new(&db.url())
+            .await
+            .expect("SqliteSecretStore should open a fresh database"),
+    );
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60).with_max_views(3))
+        .await
+        .expect("insert_secret should succeed");
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..16 {
+        let store = store.clone();
+        let id = created.id.clone();
+        tasks.spawn(async move {
+            store
+                .get_and_delete_secret(&id)
+                .await
+                .expect("concurrent read should succeed")
+                .map(|secret| secret.remaining_views)
+        });
+    }
+
+    let mut remaining = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        remaining.extend(result.expect("reader task should not panic"));
+    }
+    remaining.sort_unstable();
+    assert_eq!(
+        remaining,
+        vec![0, 1, 2],
+        "each of the three views should be handed out exactly once"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_burns_secret_after_too_many_wrong_pins() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+
+    let created = store
+        .insert_secret(
+            Secret::new("ciphertext".into(), "iv".into(), 60).with_pin_hash("pin-hash".into()),
+        )
+        .await
+        .expect("insert_secret should succeed");
+
+    let peeked = store
+        .peek_secret(&created.id)
+        .await
+        .expect("peek_secret should succeed")
+        .expect("peek should find the secret");
+    assert_eq!(peeked.pin_hash.as_deref(), Some("pin-hash"));
+
+    for expected_left in [2, 1, 0] {
+        let left = store
+            .record_failed_pin(&created.id, 3)
+            .await
+            .expect("record_failed_pin should succeed");
+        assert_eq!(left, Some(expected_left));
+    }
+
+    assert!(
+        store
+            .peek_secret(&created.id)
+            .await
+            .expect("peek_secret should succeed")
+            .is_none(),
+        "secret should be burned after the last wrong pin"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_tombstone_replaces_earlier_one_and_expires() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+    let now = time::OffsetDateTime::now_utc();
+    let expired = Tombstone::new(TombstoneReason::Expired, now + time::Duration::minutes(5));
+    let mut read = Tombstone::new(TombstoneReason::Read, now + time::Duration::minutes(5));
+    read.read_at = Some(now);
+
+    store
+        .put_tombstone("tombstoned", &expired)
+        .await
+        .expect("put_tombstone should succeed");
+    store
+        .put_tombstone("tombstoned", &read)
+        .await
+        .expect("put_tombstone should replace the earlier tombstone");
+    let found = store
+        .get_tombstone("tombstoned")
+        .await
+        .expect("get_tombstone should succeed");
+    assert_eq!(found, Some(read));
+
+    store
+        .put_tombstone(
+            "stale",
+            &Tombstone::new(TombstoneReason::Revoked, now - time::Duration::seconds(1)),
+        )
+        .await
+        .expect("put_tombstone should succeed");
+    assert!(
+        store
+            .get_tombstone("stale")
+            .await
+            .expect("get_tombstone should succeed")
+            .is_none(),
+        "an expired tombstone should not be returned"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_receipt_outlives_its_secret() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+
+    let secret = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 60))
+        .await
+        .expect("insert_secret should succeed");
+    let mut receipt = Receipt::new(
+        uuid::Uuid::new_v4().simple().to_string(),
+        &secret,
+        secret.expires_at() + time::Duration::hours(1),
+    );
+    store
+        .put_receipt(&receipt)
+        .await
+        .expect("put_receipt should succeed");
+
+    assert!(
+        store
+            .burn_secret(&secret.id)
+            .await
+            .expect("burn should succeed")
+    );
+    assert!(receipt.settle(ReceiptStatus::Revoked, None, None));
+    store
+        .put_receipt(&receipt)
+        .await
+        .expect("put_receipt should replace the earlier receipt");
+
+    let found = store
+        .get_receipt(&receipt.id)
+        .await
+        .expect("get_receipt should succeed");
+    assert_eq!(found, Some(receipt));
+}
+
+#[tokio::test]
+async fn sqlite_store_set_secret_ttl_moves_the_expiry() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+
+    let created = store
+        .insert_secret(Secret::new("ciphertext".into(), "iv".into(), 1).with_max_views(2))
+        .await
+        .expect("insert_secret should succeed");
+    store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed")
+        .expect("first view should be served");
+
+    let updated = store
+        .set_secret_ttl(&created.id, 60)
+        .await
+        .expect("set_secret_ttl should succeed")
+        .expect("secret should be found");
+    assert_eq!(updated.ttl_secs, 60);
+    assert_eq!(updated.remaining_views, 1);
+    assert_eq!(
+        updated.expires_at(),
+        created.created_at + time::Duration::seconds(60)
+    );
+
+    // Well past the original one-second TTL.
+    tokio::time::sleep(StdDuration::from_millis(1500)).await;
+    let read = store
+        .get_and_delete_secret(&created.id)
+        .await
+        .expect("read should succeed");
+    assert_eq!(read.map(|secret| secret.remaining_views), Some(0));
+
+    assert!(
+        store
+            .set_secret_ttl(&created.id, 60)
+            .await
+            .expect("set_secret_ttl should succeed")
+            .is_none(),
+        "a consumed secret cannot be extended"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_idempotency_record_is_kept_until_it_expires() {
+    let db = TempDb::new();
+    let store = SqliteSecretStore::new(&db.url())
+        .await
+        .expect("sqlite store should open");
+
+    let record = |secret_id: &str, expires_in: time::Duration| IdempotencyRecord {
+        request_hash: "request-hash".into(),
+        secret_id: secret_id.into(),
+        sealed_management_token: "sealed-management-token".into(),
+        receipt_id: None,
+        secret_expires_at: time::OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        expires_at: time::OffsetDateTime::now_utc() + expires_in,
+    };
+    let key = uuid::Uuid::new_v4().simple().to_string();
+
+    let first = record("first", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&key, &first)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+    // A live record is never replaced, only reported.
+    let existing = store
+        .put_idempotency_record(&key, &record("second", time::Duration::minutes(1)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(existing.as_ref(), Some(&first));
+    assert_eq!(
+        store
+            .get_idempotency_record(&key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        Some(first)
+    );
+
+    // An expired one may be.
+    let other_key = uuid::Uuid::new_v4().simple().to_string();
+    store
+        .put_idempotency_record(&other_key, &record("stale", time::Duration::seconds(-5)))
+        .await
+        .expect("put_idempotency_record should succeed");
+    assert_eq!(
+        store
+            .get_idempotency_record(&other_key)
+            .await
+            .expect("get_idempotency_record should succeed"),
+        None
+    );
+    let fresh = record("fresh", time::Duration::minutes(1));
+    assert_eq!(
+        store
+            .put_idempotency_record(&other_key, &fresh)
+            .await
+            .expect("put_idempotency_record should succeed"),
+        None
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_pool_stats_require_the_health_token() {
+    let db = TempDb::new();
+    let store = Arc::new(
+        SqliteSecretStore::new(&db.url())
+            .await
+            .expect("SqliteSecretStore should open a fresh database"),
+    );
+    let config = ApiConfig {
+        health_token: Some("health-token".into()),
+        ..ApiConfig::default()
+    };
+    let app = app_router_with_store_and_config(store, config);
+
+    for (authorization, expect_pool) in [
+        (None, false),
+        (Some("Bearer wrong-token"), false),
+        (Some("Bearer health-token"), true),
+    ] {
+        let mut request = Request::builder().method("GET").uri("/health/store");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let response = app
+            .clone()
+            .oneshot(
+                request
+                    .body(Body::empty())
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request to router should succeed");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body collection should succeed")
+            .to_bytes();
+        let json: Value =
+            serde_json::from_slice(&body_bytes).expect("response body should be valid JSON");
+        assert_eq!(json["status"], "ok");
+        assert_eq!(
+            json.get("pool").is_some_and(Value::is_object),
+            expect_pool,
+            "authorization {authorization:?}"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode, header},
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt; // for `oneshot`
+
+use cendre_backend::app_router_with_store;
+use cendre_backend::db::{SecretStore, StorageError, StorageResult};
+use cendre_backend::models::{IdempotencyRecord, Receipt, Secret, Tombstone};
+
+/// A store whose every operation fails with the error produced by `make_error`.
+struct FailingStore {
+    make_error: fn() -> StorageError,
+}
+
+#[async_trait]
+impl SecretStore for FailingStore {
+    async fn insert_secret(&self, _secret: Secret) -> StorageResult<Secret> {
+        Err((self.make_error)())
+    }
+
+    async fn get_and_delete_secret(&self, _id: &str) -> StorageResult<Option<Secret>> {
+        Err((self.make_error)())
+    }
+
+    async fn peek_secret(&self, _id: &str) -> StorageResult<Option<Secret>> {
+        Err((self.make_error)())
+    }
+
+    async fn record_failed_pin(&self, _id: &str, _max_attempts: u32) -> StorageResult<Option<u32>> {
+        Err((self.make_error)())
+    }
+
+    async fn burn_secret(&self, _id: &str) -> StorageResult<bool> {
+        Err((self.make_error)())
+    }
+
+    async fn set_secret_ttl(&self, _id: &str, _ttl_secs: u32) -> StorageResult<Option<Secret>> {
+        Err((self.make_error)())
+    }
+
+    async fn put_tombstone(&self, _id: &str, _tombstone: &Tombstone) -> StorageResult<()> {
+        Err((self.make_error)())
+    }
+
+    async fn get_tombstone(&self, _id: &str) -> StorageResult<Option<Tombstone>> {
+        Err((self.make_error)())
+    }
+
+    async fn put_receipt(&self, _receipt: &Receipt) -> StorageResult<()> {
+        Err((self.make_error)())
+    }
+
+    async fn get_receipt(&self, _id: &str) -> StorageResult<Option<Receipt>> {
+        Err((self.make_error)())
+    }
+
+    async fn delete_receipt(&self, _id: &str) -> StorageResult<()> {
+        Err((self.make_error)())
+    }
+
+    async fn put_idempotency_record(
+        &self,
+        _key: &str,
+        _record: &IdempotencyRecord,
+    ) -> StorageResult<Option<IdempotencyRecord>> {
+        Err((self.make_error)())
+    }
+
+    async fn get_idempotency_record(&self, _key: &str) -> StorageResult<Option<IdempotencyRecord>> {
+        Err((self.make_error)())
+    }
+
+    async fn replace_idempotency_record(
+        &self,
+        _key: &str,
+        _record: &IdempotencyRecord,
+    ) -> StorageResult<()> {
+        Err((self.make_error)())
+    }
+
+    async fn ping(&self) -> StorageResult<()> {
+        Err((self.make_error)())
+    }
+}
+
+async fn create_with(make_error: fn() -> StorageError) -> axum::response::Response {
+    let app = app_router_with_store(Arc::new(FailingStore { make_error }));
+
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri("/api/secrets")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("failed to build request"),
+    )
+    .await
+    .expect("request to router should succeed")
+}
+
+async fn error_message(response: axum::response::Response) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    json["error"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn unavailable_store_returns_503_with_retry_after() {
+    let response = create_with(|| StorageError::Unavailable("connection refused".into())).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .expect("503 must carry Retry-After");
+    assert!(retry_after.to_str().unwrap().parse::<u64>().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn storage_errors_map_to_distinct_statuses() {
+    let cases: [(fn() -> StorageError, StatusCode); 6] = [
+        (
+            || StorageError::Timeout("read timed out".into()),
+            StatusCode::GATEWAY_TIMEOUT,
+        ),
+        (
+            || StorageError::CapacityExceeded("OOM".into()),
+            StatusCode::INSUFFICIENT_STORAGE,
+        ),
+        (
+            || StorageError::Conflict("unique violation".into()),
+            StatusCode::CONFLICT,
+        ),
+        (
+            || StorageError::Serialization("bad json".into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        (
+            || StorageError::Backend("boom".into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        (
+            || StorageError::Config("bad url".into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+    ];
+
+    for (make_error, expected) in cases {
+        let response = create_with(make_error).await;
+        assert_eq!(response.status(), expected, "for {:?}", make_error());
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
+
+#[tokio::test]
+async fn storage_error_details_are_not_leaked() {
+    let response =
+        create_with(|| StorageError::Unavailable("redis://:hunter2@cache:6379 refused".into()))
+            .await;
+
+    let message = error_message(response).await;
+    assert!(!message.contains("hunter2"));
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::db::InMemorySecretStore;
+use cendre_backend::ttl::{TtlPolicy, TtlPreset};
+use cendre_backend::{
+    ApiConfig, app_router_with_in_memory_store, app_router_with_store_and_config,
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tower::ServiceExt; // for `oneshot`
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn create(payload: Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/api/secrets")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("failed to build request")
+}
+
+fn timestamp(value: &Value) -> OffsetDateTime {
+    OffsetDateTime::parse(value.as_str().expect("timestamp string"), &Rfc3339)
+        .expect("RFC 3339 timestamp")
+}
+
+fn short_policy_app() -> Router {
+    let config = ApiConfig {
+        ttl_policy: TtlPolicy {
+            min_secs: 60,
+            max_secs: 600,
+            long_max_secs: 600,
+            default_secs: 120,
+            presets: vec![
+                TtlPreset::new("1 minute", 60),
+                TtlPreset::new("10 minutes", 600),
+            ],
+        },
+        ..ApiConfig::default()
+    };
+    app_router_with_store_and_config(Arc::new(InMemorySecretStore::new()), config)
+}
+
+#[tokio::test]
+async fn policy_is_published_and_enforced() {
+    let app = short_policy_app();
+
+    let (status, policy) = send(
+        &app,
+        Request::builder()
+            .uri("/api/ttl-policy")
+            .body(Body::empty())
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(policy["min_secs"], 60);
+    assert_eq!(policy["max_secs"], 600);
+    assert_eq!(policy["default_secs"], 120);
+    assert_eq!(policy["long_tier_available"], false);
+    assert_eq!(policy["presets"][1]["name"], "10 minutes");
+    assert_eq!(policy["presets"][1]["ttl_secs"], 600);
+
+    for ttl_secs in [59, 601] {
+        let (status, _) = send(
+            &app,
+            create(serde_json::json!({
+                "ciphertext": "ciphertext-value",
+                "iv": "iv-value",
+                "ttl_secs": ttl_secs,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "for ttl_secs {ttl_secs}");
+    }
+
+    // Without a TTL, the configured default applies.
+    let before = OffsetDateTime::now_utc();
+    let (status, created) = send(
+        &app,
+        create(serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let expires_at = timestamp(&created["expires_at"]);
+    assert!(expires_at >= before + time::Duration::seconds(120));
+    assert!(expires_at <= OffsetDateTime::now_utc() + time::Duration::seconds(120));
+}
+
+#[tokio::test]
+async fn absolute_expiry_is_honoured_and_reported() {
+    let app = app_router_with_in_memory_store();
+    let wanted = OffsetDateTime::now_utc() + time::Duration::minutes(30);
+
+    let (status, created) = send(
+        &app,
+        create(serde_json::json!({
+            "ciphertext": "ciphertext-value",
+            "iv": "iv-value",
+            "expires_at": wanted.format(&Rfc3339).unwrap(),
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    // Rounded up to whole seconds, never earlier than asked for.
+    let expires_at = timestamp(&created["expires_at"]);
+    assert!(expires_at >= wanted && expires_at < wanted + time::Duration::seconds(2));
+
+    let id = created["id"].as_str().unwrap();
+    let (status, revealed) = send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/secret/{id}/reveal"))
+            .body(Body::empty())
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revealed["expires_at"], created["expires_at"]);
+    assert_eq!(revealed["ttl_secs"], 1800);
+}
+
+#[tokio::test]
+async fn absolute_expiry_must_be_valid() {
+    let app = app_router_with_in_memory_store();
+    let now = OffsetDateTime::now_utc();
+
+    let cases = [
+        // Both forms at once.
+        serde_json::json!({
+            "ttl_secs": 60u32,
+            "expires_at": (now + time::Duration::minutes(5)).format(&Rfc3339).unwrap(),
+        }),
+        // In the past.
+        serde_json::json!({
+            "expires_at": (now - time::Duration::minutes(5)).format(&Rfc3339).unwrap(),
+        }),
+        // Beyond the maximum TTL.
+        serde_json::json!({
+            "expires_at": (now + time::Duration::days(2)).format(&Rfc3339).unwrap(),
+        }),
+    ];
+
+    for mut payload in cases {
+        payload["ciphertext"] = "ciphertext-value".into();
+        payload["iv"] = "iv-value".into();
+        let (status, _) = send(&app, create(payload.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "for {payload}");
+    }
+}
@@ -0,0 +1,145 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use cendre_backend::app_router_with_in_memory_store;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tower::ServiceExt; // for `oneshot`
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request to router should succeed");
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn create_secret(app: &Router) -> Value {
+    let payload = serde_json::json!({
+        "ciphertext": "ciphertext-value",
+        "iv": "iv-value",
+        "ttl_secs": 60u32,
+    });
+    let (status, created) = send(
+        app,
+        Request::builder()
+            .method("POST")
+            .uri("/api/secrets")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    created
+}
+
+fn update(id: &str, token: Option<&str>, ttl_secs: u32) -> Request<Body> {
+    let mut request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/secret/{id}"))
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    request
+        .body(Body::from(
+            serde_json::json!({ "ttl_secs": ttl_secs }).to_string(),
+        ))
+        .expect("failed to build request")
+}
+
+fn timestamp(value: &Value) -> OffsetDateTime {
+    OffsetDateTime::parse(value.as_str().expect("timestamp string"), &Rfc3339)
+        .expect("RFC 3339 timestamp")
+}
+
+#[tokio::test]
+async fn sender_can_extend_and_shorten_the_ttl() {
+    let app = app_router_with_in_memory_store();
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+
+    let (status, extended) = send(&app, update(id, Some(token), 3600)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(extended["id"], id);
+    assert_eq!(extended["ttl_secs"], 3600);
+    let extended_at = timestamp(&extended["expires_at"]);
+    let remaining = extended_at - OffsetDateTime::now_utc();
+    assert!(remaining > time::Duration::minutes(59) && remaining <= time::Duration::hours(1));
+
+    let (status, shortened) = send(&app, update(id, Some(token), 30)).await;
+    assert_eq!(status, StatusCode::OK);
+    // The TTL still counts from creation.
+    assert_eq!(
+        timestamp(&shortened["expires_at"]),
+        extended_at - time::Duration::seconds(3600 - 30)
+    );
+
+    // The receipt follows the secret's new expiry.
+    let receipt_id = created["receipt_id"].as_str().unwrap();
+    let (status, receipt) = send(
+        &app,
+        Request::builder()
+            .uri(format!("/api/receipt/{receipt_id}"))
+            .body(Body::empty())
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(receipt["secret_expires_at"], shortened["expires_at"]);
+
+    let (status, metadata) = send(
+        &app,
+        Request::builder()
+            .uri(format!("/api/secret/{id}"))
+            .body(Body::empty())
+            .expect("failed to build request"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(metadata["expires_at"], shortened["expires_at"]);
+}
+
+#[tokio::test]
+async fn ttl_update_requires_the_management_token() {
+    let app = app_router_with_in_memory_store();
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+
+    let (status, _) = send(&app, update(id, None, 3600)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(&app, update(id, Some("not-the-token"), 3600)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(&app, update("missing", Some("whatever"), 3600)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn ttl_update_stays_within_policy() {
+    let app = app_router_with_in_memory_store();
+    let created = create_secret(&app).await;
+    let id = created["id"].as_str().unwrap();
+    let token = created["management_token"].as_str().unwrap();
+
+    for ttl_secs in [0, 86_401] {
+        let (status, _) = send(&app, update(id, Some(token), ttl_secs)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "for ttl_secs {ttl_secs}");
+    }
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    // Already older than a second, so this would expire it on the spot.
+    let (status, body) = send(&app, update(id, Some(token), 1)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("revoke"));
+}
@@ -37,6 +37,11 @@ test("full secret flow with one-time read and key staying client-side", async ({
 
   await readerPage.goto(fullUrl);
 
+  // Opening the link alone must not burn the secret; the reader confirms first.
+  await readerPage
+    .getByRole("button", { name: /REVEAL MESSAGE/i })
+    .click();
+
   await expect(
     readerPage.getByText(
       /This message has been permanently deleted from the server/i
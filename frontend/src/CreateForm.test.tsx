@@ -35,7 +35,60 @@ describe("CreateForm", () => {
 
     // With the new UX the button is simply disabled until there is input.
     expect(button).toBeDisabled();
-    expect(globalThis.fetch).not.toHaveBeenCalled();
+    expect(globalThis.fetch).not.toHaveBeenCalledWith(
+      "/api/secrets",
+      expect.anything()
+    );
+  });
+
+  it("offers the server's TTL presets and preselects its default", async () => {
+    globalThis.fetch = vi.fn().mockResolvedValue({
+      ok: true,
+      status: 200,
+      json: async () => ({
+        min_secs: 60,
+        max_secs: 604800,
+        long_max_secs: 604800,
+        default_secs: 86400,
+        presets: [
+          { name: "1 hour", ttl_secs: 3600 },
+          { name: "1 day", ttl_secs: 86400 },
+          { name: "1 week", ttl_secs: 604800 }
+        ],
+        long_tier_available: false
+      })
+    });
+
+    render(<CreateForm />);
+
+    expect(await screen.findByRole("option", { name: "1 week" })).toBeInTheDocument();
+    expect(screen.queryByRole("option", { name: "5 minutes" })).not.toBeInTheDocument();
+    expect(screen.getByLabelText(/CONFIG::TIME_TO_LIVE/i)).toHaveValue("86400");
+    expect(globalThis.fetch).toHaveBeenCalledWith("/api/ttl-policy");
+  });
+
+  it("only offers long-tier presets when the server can hold them", async () => {
+    globalThis.fetch = vi.fn().mockResolvedValue({
+      ok: true,
+      status: 200,
+      json: async () => ({
+        min_secs: 1,
+        max_secs: 86400,
+        long_max_secs: 2592000,
+        default_secs: 3600,
+        presets: [
+          { name: "1 hour", ttl_secs: 3600 },
+          { name: "30 days", ttl_secs: 2592000 }
+        ],
+        long_tier_available: false
+      })
+    });
+
+    render(<CreateForm />);
+
+    expect(await screen.findByRole("option", { name: "1 hour" })).toBeInTheDocument();
+    expect(screen.queryByRole("option", { name: "5 minutes" })).not.toBeInTheDocument();
+    expect(screen.queryByRole("option", { name: "30 days" })).not.toBeInTheDocument();
   });
 
   it("encrypts the message, posts to API, and shows a one-time URL", async () => {
@@ -47,14 +100,18 @@ describe("CreateForm", () => {
       tokenB64Url: "token-fragment"
     });
 
-    const fetchMock = vi.fn().mockResolvedValue({
-      ok: true,
-      status: 201,
-      json: async () => ({
-        id: "abc123",
-        expires_at: "2025-01-01T00:00:00.000Z"
-      })
-    });
+    const fetchMock = vi.fn().mockImplementation(async (url: string) =>
+      url === "/api/secrets"
+        ? {
+            ok: true,
+            status: 201,
+            json: async () => ({
+              id: "abc123",
+              expires_at: "2025-01-01T00:00:00.000Z"
+            })
+          }
+        : { ok: false, status: 404, json: async () => ({}) }
+    );
     globalThis.fetch = fetchMock;
 
     render(<CreateForm />);
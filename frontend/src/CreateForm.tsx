@@ -10,14 +10,53 @@ function sleep(ms: number) {
 
 interface ApiResponse {
   id: string;
+  expires_at?: string;
 }
 
-const DEFAULT_TTL_SECS =
-  Number(import.meta.env.VITE_DEFAULT_TTL_SECS) || 3600;
+interface TtlPreset {
+  name: string;
+  ttl_secs: number;
+}
+
+interface TtlPolicy {
+  max_secs: number;
+  default_secs: number;
+  presets: TtlPreset[];
+  // Presets above max_secs need a durable store on the server.
+  long_tier_available: boolean;
+}
+
+// Offered until the server's policy (GET /api/ttl-policy) has loaded, or if
+// it cannot be fetched. Matches the server's built-in defaults.
+const FALLBACK_TTL_POLICY: TtlPolicy = {
+  max_secs: 86400,
+  default_secs: 3600,
+  presets: [
+    { name: "5 minutes", ttl_secs: 300 },
+    { name: "1 hour", ttl_secs: 3600 },
+    { name: "24 hours", ttl_secs: 86400 }
+  ],
+  long_tier_available: false
+};
+
+function isTtlPolicy(value: unknown): value is TtlPolicy {
+  const policy = value as TtlPolicy | null;
+  return (
+    typeof policy?.default_secs === "number" &&
+    typeof policy.max_secs === "number" &&
+    typeof policy.long_tier_available === "boolean" &&
+    Array.isArray(policy.presets) &&
+    policy.presets.every(
+      preset =>
+        typeof preset?.name === "string" && typeof preset.ttl_secs === "number"
+    )
+  );
+}
 
 export function CreateForm() {
   const [secret, setSecret] = useState("");
-  const [ttlSecs, setTtlSecs] = useState<number>(DEFAULT_TTL_SECS);
+  const [ttlPolicy, setTtlPolicy] = useState<TtlPolicy>(FALLBACK_TTL_POLICY);
+  const [ttlSecs, setTtlSecs] = useState<number>(FALLBACK_TTL_POLICY.default_secs);
   const [error, setError] = useState<string | null>(null);
   const [apiError, setApiError] = useState<string | null>(null);
   const [resultUrl, setResultUrl] = useState<string | null>(null);
@@ -30,6 +69,41 @@ export function CreateForm() {
     setCharCount(secret.length);
   }, [secret]);
 
+  useEffect(() => {
+    let cancelled = false;
+
+    async function loadTtlPolicy() {
+      try {
+        const response = await fetch("/api/ttl-policy");
+        if (!response.ok) return;
+        const json: unknown = await response.json();
+        if (!cancelled && isTtlPolicy(json)) {
+          setTtlPolicy(json);
+          setTtlSecs(json.default_secs);
+        }
+      } catch {
+        // Keep the fallback presets.
+      }
+    }
+
+    void loadTtlPolicy();
+    return () => {
+      cancelled = true;
+    };
+  }, []);
+
+  const offeredPresets = ttlPolicy.presets.filter(
+    preset => preset.ttl_secs <= ttlPolicy.max_secs || ttlPolicy.long_tier_available
+  );
+  const ttlOptions = offeredPresets.some(
+    preset => preset.ttl_secs === ttlPolicy.default_secs
+  )
+    ? offeredPresets
+    : [
+        { name: `${ttlPolicy.default_secs} seconds`, ttl_secs: ttlPolicy.default_secs },
+        ...offeredPresets
+      ];
+
   const addTerminalLine = (line: string) => {
     setTerminalLines(prev => [...prev, `[${new Date().toLocaleTimeString()}] ${line}`]);
   };
@@ -93,6 +167,9 @@ export function CreateForm() {
       addTerminalLine("SUCCESS: Secure link generated.");
       addTerminalLine(`HASH: ${json.id.substring(0, 8)}...`);
       addTerminalLine(`TTL: ${ttlSecs} seconds`);
+      if (json.expires_at) {
+        addTerminalLine(`EXPIRES: ${new Date(json.expires_at).toLocaleString()}`);
+      }
       addTerminalLine("STATUS: Ready for transmission.");
       
       setResultUrl(url);
@@ -174,9 +251,11 @@ export function CreateForm() {
               className="w-full rounded bg-black/80 border border-terminal-green/50 px-3 py-2 text-sm text-terminal-green focus:outline-none focus:border-terminal-green focus:shadow-terminal-glow-sm font-mono appearance-none cursor-pointer terminal-input"
               disabled={submitting}
             >
-              <option value={300}>5 minutes</option>
-              <option value={3600}>1 hour</option>
-              <option value={86400}>24 hours</option>
+              {ttlOptions.map(preset => (
+                <option key={preset.ttl_secs} value={preset.ttl_secs}>
+                  {preset.name}
+                </option>
+              ))}
             </select>
           </div>
 
@@ -1,4 +1,5 @@
 import { render, screen } from "@testing-library/react";
+import userEvent from "@testing-library/user-event";
 import { MemoryRouter, Route, Routes } from "react-router-dom";
 import { beforeEach, describe, expect, it, vi } from "vitest";
 import { ReadView } from "./ReadView";
@@ -12,6 +13,7 @@ import { decryptWithToken } from "./lib/crypto";
 describe("ReadView", () => {
   beforeEach(() => {
     vi.restoreAllMocks();
+    sessionStorage.clear();
     // @ts-expect-error - allow assigning fetch mock in tests
     global.fetch = vi.fn();
   });
@@ -35,19 +37,31 @@ describe("ReadView", () => {
     expect(fetchMock).not.toHaveBeenCalled();
   });
 
-  it("fetches and decrypts the secret when id and key are present", async () => {
-    const fetchMock = vi.fn().mockResolvedValue({
-      ok: true,
-      status: 200,
-      json: async () => ({
-        ciphertext: "ciphertext-b64",
-        iv: "iv-b64"
+  it("only reveals and decrypts the secret after the reader confirms", async () => {
+    const fetchMock = vi
+      .fn()
+      .mockResolvedValueOnce({
+        ok: true,
+        status: 200,
+        json: async () => ({
+          id: "abc123",
+          expires_at: "2026-10-17T12:00:00Z",
+          remaining_views: 1
+        })
       })
-    });
+      .mockResolvedValueOnce({
+        ok: true,
+        status: 200,
+        json: async () => ({
+          ciphertext: "ciphertext-b64",
+          iv: "iv-b64"
+        })
+      });
     // @ts-expect-error - assigning fetch mock
     global.fetch = fetchMock;
 
     (decryptWithToken as unknown as vi.Mock).mockResolvedValue("hello secret");
+    const user = userEvent.setup();
 
     render(
       <MemoryRouter initialEntries={["/s/abc123#encoded-key"]}>
@@ -57,15 +71,25 @@ describe("ReadView", () => {
       </MemoryRouter>
     );
 
-    expect(await screen.findByText(/hello secret/i)).toBeInTheDocument();
-
+    const revealButton = await screen.findByRole("button", {
+      name: /REVEAL MESSAGE/i
+    });
+    // Loading the page only looks the secret up; it must not consume it.
+    expect(fetchMock).toHaveBeenCalledTimes(1);
     expect(fetchMock).toHaveBeenCalledWith("/api/secret/abc123");
+
+    await user.click(revealButton);
+
+    expect(await screen.findByText(/hello secret/i)).toBeInTheDocument();
+    expect(fetchMock).toHaveBeenLastCalledWith("/api/secret/abc123/reveal", {
+      method: "POST"
+    });
   });
 
-  it("shows destroyed/expired message on 404", async () => {
+  it.each([404, 410])("shows destroyed/expired message on %i", async (status) => {
     const fetchMock = vi.fn().mockResolvedValue({
       ok: false,
-      status: 404,
+      status,
       json: async () => ({})
     });
     // @ts-expect-error - assigning fetch mock
@@ -83,6 +107,122 @@ describe("ReadView", () => {
       await screen.findByText(/Message has been consumed or expired/i)
     ).toBeInTheDocument();
   });
-});
 
+  it("shows when an embargoed secret unlocks on 423", async () => {
+    const fetchMock = vi.fn().mockResolvedValue({
+      ok: false,
+      status: 423,
+      json: async () => ({ unlock_at: "2030-01-01T09:00:00Z" })
+    });
+    // @ts-expect-error - assigning fetch mock
+    global.fetch = fetchMock;
+
+    render(
+      <MemoryRouter initialEntries={["/s/abc123#encoded-key"]}>
+        <Routes>
+          <Route path="/s/:id" element={<ReadView />} />
+        </Routes>
+      </MemoryRouter>
+    );
+
+    expect(await screen.findByText(/Message is embargoed/i)).toBeInTheDocument();
+    expect(screen.getByText(/AVAILABLE FROM/i)).toBeInTheDocument();
+  });
+
+  it("re-fetches with a stored claim token instead of looking the secret up", async () => {
+    sessionStorage.setItem("cendre:claim:abc123", "claim-token");
+    const fetchMock = vi.fn().mockResolvedValue({
+      ok: true,
+      status: 200,
+      json: async () => ({
+        ciphertext: "ciphertext-b64",
+        iv: "iv-b64",
+        claim_token: "claim-token"
+      })
+    });
+    // @ts-expect-error - assigning fetch mock
+    global.fetch = fetchMock;
+
+    (decryptWithToken as unknown as vi.Mock).mockResolvedValue("hello again");
+    const user = userEvent.setup();
 
+    render(
+      <MemoryRouter initialEntries={["/s/abc123#encoded-key"]}>
+        <Routes>
+          <Route path="/s/:id" element={<ReadView />} />
+        </Routes>
+      </MemoryRouter>
+    );
+
+    await user.click(await screen.findByRole("button", { name: /REVEAL MESSAGE/i }));
+
+    expect(await screen.findByText(/hello again/i)).toBeInTheDocument();
+    expect(fetchMock).toHaveBeenCalledTimes(1);
+    expect(fetchMock).toHaveBeenCalledWith("/api/secret/abc123/reveal", {
+      method: "POST",
+      headers: { "X-Claim-Token": "claim-token" }
+    });
+  });
+
+  it("asks for the PIN and sends it with the reveal", async () => {
+    const fetchMock = vi
+      .fn()
+      .mockResolvedValueOnce({
+        ok: true,
+        status: 200,
+        json: async () => ({
+          id: "abc123",
+          expires_at: "2026-10-17T12:00:00Z",
+          remaining_views: 1,
+          pin_required: true
+        })
+      })
+      .mockResolvedValueOnce({
+        ok: false,
+        status: 403,
+        json: async () => ({ error: "incorrect pin", attempts_left: 4 })
+      })
+      .mockResolvedValueOnce({
+        ok: true,
+        status: 200,
+        json: async () => ({
+          ciphertext: "ciphertext-b64",
+          iv: "iv-b64"
+        })
+      });
+    // @ts-expect-error - assigning fetch mock
+    global.fetch = fetchMock;
+
+    (decryptWithToken as unknown as vi.Mock).mockResolvedValue("pin protected");
+    const user = userEvent.setup();
+
+    render(
+      <MemoryRouter initialEntries={["/s/abc123#encoded-key"]}>
+        <Routes>
+          <Route path="/s/:id" element={<ReadView />} />
+        </Routes>
+      </MemoryRouter>
+    );
+
+    const revealButton = await screen.findByRole("button", { name: /REVEAL MESSAGE/i });
+    expect(revealButton).toBeDisabled();
+
+    await user.type(screen.getByLabelText(/INPUT::PIN/i), "0000");
+    await user.click(revealButton);
+
+    expect(await screen.findByText(/ATTEMPTS LEFT: 4/i)).toBeInTheDocument();
+    expect(fetchMock).toHaveBeenLastCalledWith("/api/secret/abc123/reveal", {
+      method: "POST",
+      headers: { "X-Secret-PIN": "0000" }
+    });
+
+    await user.type(screen.getByLabelText(/INPUT::PIN/i), "1234");
+    await user.click(screen.getByRole("button", { name: /REVEAL MESSAGE/i }));
+
+    expect(await screen.findByText(/pin protected/i)).toBeInTheDocument();
+    expect(fetchMock).toHaveBeenLastCalledWith("/api/secret/abc123/reveal", {
+      method: "POST",
+      headers: { "X-Secret-PIN": "1234" }
+    });
+  });
+});
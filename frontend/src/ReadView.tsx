@@ -9,11 +9,33 @@ function sleep(ms: number) {
   return new Promise(resolve => setTimeout(resolve, ms));
 }
 
-type ViewState = "idle" | "loading" | "ready" | "missing-key" | "expired" | "error";
+type ViewState =
+  | "idle"
+  | "checking"
+  | "confirm"
+  | "loading"
+  | "ready"
+  | "missing-key"
+  | "expired"
+  | "locked"
+  | "error";
+
+interface SecretMetadata {
+  expires_at: string;
+  remaining_views: number;
+  pin_required?: boolean;
+}
 
 interface SecretResponse {
   ciphertext: string;
   iv: string;
+  claim_token?: string;
+}
+
+// Grace-mode reads hand out a claim token that lets this tab fetch the
+// secret again for a short while, e.g. after a crash or reload.
+function claimStorageKey(secretId: string) {
+  return `cendre:claim:${secretId}`;
 }
 
 export function ReadView() {
@@ -21,10 +43,15 @@ export function ReadView() {
   const location = useLocation();
 
   const [state, setState] = useState<ViewState>("idle");
+  const [metadata, setMetadata] = useState<SecretMetadata | null>(null);
+  const [unlockAt, setUnlockAt] = useState<string | null>(null);
   const [plaintext, setPlaintext] = useState<string | null>(null);
   const [decryptionSteps, setDecryptionSteps] = useState<string[]>([]);
   const [showContent, setShowContent] = useState(false);
   const [copied, setCopied] = useState(false);
+  const [pinRequired, setPinRequired] = useState(false);
+  const [pin, setPin] = useState("");
+  const [pinError, setPinError] = useState<string | null>(null);
 
   const addDecryptionStep = (step: string) => {
     setDecryptionSteps(prev => [...prev, step]);
@@ -41,6 +68,8 @@ export function ReadView() {
     }
   };
 
+  // Only look the secret up on load. Revealing it burns a view, so that waits
+  // for an explicit click; link previewers never get that far.
   useEffect(() => {
     const secretId = id;
     if (!secretId) {
@@ -54,68 +83,141 @@ export function ReadView() {
       return;
     }
 
+    // Already read here in grace mode: the server would only say it is gone.
+    if (sessionStorage.getItem(claimStorageKey(secretId))) {
+      setState("confirm");
+      return;
+    }
+
     let cancelled = false;
 
-    async function fetchAndDecrypt() {
+    async function checkSecret() {
       try {
-        setState("loading");
-        setDecryptionSteps([]);
-        
-        addDecryptionStep("INITIATING: Secure connection...");
-        await sleep(400);
-        
-        addDecryptionStep(`REQUESTING: Secret ID ${secretId?.substring(0, 8) || 'unknown'}...`);
+        setState("checking");
         const response = await fetch(`/api/secret/${secretId}`);
 
-        if (response.status === 404) {
-          addDecryptionStep("ERROR: Secret not found or already consumed.");
+        if (response.status === 404 || response.status === 410) {
           if (!cancelled) setState("expired");
           return;
         }
 
+        if (response.status === 423) {
+          const json = (await response.json()) as { unlock_at?: string };
+          if (!cancelled) {
+            setUnlockAt(json.unlock_at ?? null);
+            setState("locked");
+          }
+          return;
+        }
+
         if (!response.ok) {
-          addDecryptionStep("ERROR: Failed to retrieve encrypted data.");
           if (!cancelled) setState("error");
           return;
         }
 
-        addDecryptionStep("RECEIVED: Encrypted payload.");
-        await sleep(300);
-        
-        const json = (await response.json()) as SecretResponse;
-        
-        addDecryptionStep("DERIVING: AES key from fragment token...");
-        await sleep(500);
-        const message = await decryptWithToken(
-          json.ciphertext,
-          json.iv,
-          hash
-        );
-
-        addDecryptionStep("SUCCESS: Message decrypted successfully.");
-        addDecryptionStep("WARNING: This message has been permanently deleted from server.");
-        
-        if (!cancelled) {
-          setPlaintext(message);
-          setState("ready");
-          setTimeout(() => setShowContent(true), 500);
-        }
-      } catch (error) {
-        addDecryptionStep("CRITICAL ERROR: Decryption failed.");
-        addDecryptionStep("POSSIBLE CAUSES: Invalid key or corrupted data.");
+        const json = (await response.json()) as SecretMetadata;
         if (!cancelled) {
-          setState("error");
+          setMetadata(json);
+          setPinRequired(json.pin_required ?? false);
+          setState("confirm");
         }
+      } catch {
+        if (!cancelled) setState("error");
       }
     }
 
-    fetchAndDecrypt();
+    checkSecret();
 
     return () => {
       cancelled = true;
     };
   }, [id, location.hash]);
 
+  const handleReveal = async () => {
+    const secretId = id;
+    const hash = location.hash?.slice(1) ?? "";
+    if (!secretId || !hash) return;
+
+    try {
+      setState("loading");
+      setDecryptionSteps([]);
+
+      addDecryptionStep("INITIATING: Secure connection...");
+      await sleep(400);
+
+      addDecryptionStep(`REQUESTING: Secret ID ${secretId.substring(0, 8)}...`);
+      const claimToken = sessionStorage.getItem(claimStorageKey(secretId));
+      const headers: Record<string, string> = {};
+      if (claimToken) headers["X-Claim-Token"] = claimToken;
+      if (pinRequired && pin) headers["X-Secret-PIN"] = pin;
+      const response = await fetch(
+        `/api/secret/${secretId}/reveal`,
+        Object.keys(headers).length > 0 ? { method: "POST", headers } : { method: "POST" }
+      );
+
+      // The sender protected this secret with a PIN; ask for it and try again.
+      if (response.status === 401) {
+        setPinRequired(true);
+        setPinError(pin ? "PIN REJECTED" : "PIN REQUIRED");
+        setState("confirm");
+        return;
+      }
+
+      if (response.status === 403) {
+        const json = (await response.json()) as { attempts_left?: number };
+        if (json.attempts_left === 0) {
+          addDecryptionStep("ERROR: Too many incorrect PINs. Message destroyed.");
+          setState("expired");
+          return;
+        }
+        setPin("");
+        setPinError(
+          json.attempts_left === undefined
+            ? "INCORRECT PIN"
+            : `INCORRECT PIN | ATTEMPTS LEFT: ${json.attempts_left}`
+        );
+        setState("confirm");
+        return;
+      }
+
+      if (response.status === 404 || response.status === 410) {
+        sessionStorage.removeItem(claimStorageKey(secretId));
+        addDecryptionStep("ERROR: Secret not found or already consumed.");
+        setState("expired");
+        return;
+      }
+
+      if (!response.ok) {
+        addDecryptionStep("ERROR: Failed to retrieve encrypted data.");
+        setState("error");
+        return;
+      }
+
+      addDecryptionStep("RECEIVED: Encrypted payload.");
+      await sleep(300);
+
+      const json = (await response.json()) as SecretResponse;
+      if (json.claim_token) {
+        sessionStorage.setItem(claimStorageKey(secretId), json.claim_token);
+      }
+
+      addDecryptionStep("DERIVING: AES key from fragment token...");
+      await sleep(500);
+      const message = await decryptWithToken(json.ciphertext, json.iv, hash);
+
+      addDecryptionStep("SUCCESS: Message decrypted successfully.");
+      addDecryptionStep("WARNING: This message has been permanently deleted from server.");
+
+      setPlaintext(message);
+      setState("ready");
+      setTimeout(() => setShowContent(true), 500);
+    } catch {
+      addDecryptionStep("CRITICAL ERROR: Decryption failed.");
+      addDecryptionStep("POSSIBLE CAUSES: Invalid key or corrupted data.");
+      setState("error");
+    }
+  };
+
   // Missing Key Error
   if (state === "missing-key") {
     return (
@@ -164,6 +266,25 @@ export function ReadView() {
     );
   }
 
+  if (state === "locked") {
+    return (
+      <div className="space-y-4">
+        <div className="rounded border border-terminal-amber bg-terminal-amber/10 p-4">
+          <div className="text-terminal-amber font-mono">
+            <div className="text-sm font-bold mb-2">
+              <span className="animate-pulse">⧗</span> MESSAGE LOCKED
+            </div>
+            <div className="text-xs space-y-1 text-terminal-amber-dim">
+              <div>STATUS: Message is embargoed by its sender</div>
+              {unlockAt && <div>AVAILABLE FROM: {new Date(unlockAt).toLocaleString()}</div>}
+              <div>RECOMMENDATION: Reopen this link once the embargo lifts</div>
+            </div>
+          </div>
+        </div>
+      </div>
+    );
+  }
+
   // General Error
   if (state === "error") {
     return (
@@ -184,8 +305,58 @@ export function ReadView() {
     );
   }
 
+  // Secret is live; wait for the recipient before burning it
+  if (state === "confirm") {
+    return (
+      <div className="space-y-4">
+        <div className="rounded border border-terminal-green bg-terminal-green/5 p-4">
+          <div className="text-terminal-green font-mono">
+            <div className="text-sm font-bold mb-2">
+              <span className="animate-pulse">◉</span> ENCRYPTED MESSAGE READY
+            </div>
+            <div className="text-xs space-y-1 text-terminal-green">
+              <div>STATUS: Sealed, not yet opened</div>
+              {metadata && (
+                <>
+                  <div>EXPIRES: {new Date(metadata.expires_at).toLocaleString()}</div>
+                  <div>VIEWS LEFT: {metadata.remaining_views}</div>
+                </>
+              )}
+              <div className="text-terminal-amber">
+                WARNING: Revealing the message deletes it from the server
+              </div>
+            </div>
+          </div>
+        </div>
+        {pinRequired && (
+          <div className="space-y-2 font-mono">
+            <label htmlFor="secret-pin" className="text-sm text-terminal-green terminal-text">
+              <span className="text-terminal-cyan">▸</span> INPUT::PIN
+            </label>
+            <input
+              id="secret-pin"
+              type="password"
+              autoComplete="off"
+              value={pin}
+              onChange={e => setPin(e.target.value)}
+              className="w-full rounded bg-black/80 border border-terminal-green px-3 py-2 text-xs font-mono text-terminal-green"
+            />
+            {pinError && <div className="text-xs text-terminal-red">{pinError}</div>}
+          </div>
+        )}
+        <button
+          onClick={handleReveal}
+          disabled={pinRequired && !pin}
+          className="w-full text-sm text-terminal-green font-mono px-4 py-2 border border-terminal-green rounded hover:bg-terminal-green/10 transition-all disabled:opacity-50 disabled:cursor-not-allowed"
+        >
+          [REVEAL MESSAGE]
+        </button>
+      </div>
+    );
+  }
+
   // Loading State
-  if (state === "loading" || state === "idle") {
+  if (state === "loading" || state === "checking" || state === "idle") {
     return (
       <div className="space-y-4">
         <div className="text-terminal-green font-mono">